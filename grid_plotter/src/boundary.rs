@@ -0,0 +1,133 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConditionKind {
+    Dirichlet,
+    Neumann,
+    Robin,
+}
+
+impl ConditionKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ConditionKind::Dirichlet => "Dirichlet",
+            ConditionKind::Neumann => "Neumann",
+            ConditionKind::Robin => "Robin",
+        }
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct BoundaryValue {
+    pub type_id: usize,
+    pub values: Vec<f64>,
+}
+
+pub type EdgeValues = HashMap<(usize, usize), BoundaryValue>;
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Conditions {
+    pub dirichlet: HashMap<usize, BoundaryValue>,
+    pub neumann: EdgeValues,
+    pub robin: EdgeValues,
+}
+
+pub struct TypeSummary {
+    pub count: usize,
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
+impl Conditions {
+    pub fn get(&self, kind: ConditionKind, nodes: &[usize]) -> Option<&BoundaryValue> {
+        match kind {
+            ConditionKind::Dirichlet => self.dirichlet.get(&nodes[0]),
+            ConditionKind::Neumann => self.neumann.get(&edge_key(nodes)),
+            ConditionKind::Robin => self.robin.get(&edge_key(nodes)),
+        }
+    }
+
+    pub fn summary<'a>(
+        &self,
+        entries: impl Iterator<Item = (ConditionKind, &'a [usize])>,
+    ) -> BTreeMap<(ConditionKind, usize), TypeSummary> {
+        let mut summary: BTreeMap<(ConditionKind, usize), TypeSummary> = BTreeMap::new();
+        for (kind, nodes) in entries {
+            let value = self.get(kind, nodes);
+            let entry = summary
+                .entry((kind, value.map_or(0, |value| value.type_id)))
+                .or_insert(TypeSummary {
+                    count: 0,
+                    minimum: f64::INFINITY,
+                    maximum: f64::NEG_INFINITY,
+                });
+            entry.count += 1;
+            for &value in value.iter().flat_map(|value| &value.values) {
+                entry.minimum = entry.minimum.min(value);
+                entry.maximum = entry.maximum.max(value);
+            }
+        }
+        summary
+    }
+}
+
+pub fn element_edges(element: &[usize]) -> Vec<Vec<usize>> {
+    match element.len() {
+        9 | 10 => (0..4)
+            .map(|side| {
+                vec![
+                    element[2 * side],
+                    element[2 * side + 1],
+                    element[(2 * side + 2) % 8],
+                ]
+            })
+            .collect(),
+        5 => (0..4)
+            .map(|side| vec![element[side], element[(side + 1) % 4]])
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn edge_key(edge: &[usize]) -> (usize, usize) {
+    let first = edge[0];
+    let last = edge[edge.len() - 1];
+    (first.min(last), first.max(last))
+}
+
+pub fn find_boundary_edges(elements: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+
+    for element in elements {
+        for edge in element_edges(element) {
+            *counts.entry(edge_key(&edge)).or_insert(0) += 1;
+        }
+    }
+
+    elements
+        .iter()
+        .flat_map(|element| element_edges(element))
+        .filter(|edge| counts[&edge_key(edge)] == 1)
+        .collect()
+}
+
+pub fn find_uncovered_edges(
+    boundary_edges: &[Vec<usize>],
+    dirichlet: &[usize],
+    neumann: &[Vec<usize>],
+) -> Vec<Vec<usize>> {
+    let dirichlet: HashSet<usize> = dirichlet.iter().copied().collect();
+    let neumann: HashSet<(usize, usize)> = neumann
+        .iter()
+        .filter(|edge| edge.len() >= 2)
+        .map(|edge| edge_key(edge))
+        .collect();
+
+    boundary_edges
+        .iter()
+        .filter(|edge| {
+            !edge.iter().all(|node| dirichlet.contains(node)) && !neumann.contains(&edge_key(edge))
+        })
+        .cloned()
+        .collect()
+}
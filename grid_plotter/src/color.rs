@@ -0,0 +1,31 @@
+use eframe::egui::Color32;
+
+pub fn interpolate_heat_color(value: f64, max: f64, min: f64) -> Color32 {
+    let normalized = (value - min) / (max - min);
+    let r = (normalized * 255.0) as u8;
+    let b = ((1.0 - normalized) * 255.0) as u8;
+    Color32::from_rgb(r, 0, b)
+}
+
+pub fn band_value(value: f64, max: f64, min: f64, count: u16) -> f64 {
+    let count = count.max(1) as f64;
+    let band = ((value - min) / (max - min) * count)
+        .floor()
+        .clamp(0.0, count - 1.0);
+    min + (band + 0.5) / count * (max - min)
+}
+
+const CATEGORY_COLORS: [Color32; 8] = [
+    Color32::from_rgb(128, 128, 128),
+    Color32::from_rgb(230, 25, 75),
+    Color32::from_rgb(60, 180, 75),
+    Color32::from_rgb(0, 130, 200),
+    Color32::from_rgb(245, 130, 48),
+    Color32::from_rgb(145, 30, 180),
+    Color32::from_rgb(70, 240, 240),
+    Color32::from_rgb(240, 50, 230),
+];
+
+pub fn category_color(id: usize) -> Color32 {
+    CATEGORY_COLORS[id % CATEGORY_COLORS.len()]
+}
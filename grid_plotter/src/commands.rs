@@ -0,0 +1,134 @@
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Command {
+    ToggleHeatmap,
+    ToggleContours,
+    TogglePoints,
+    ToggleNumbers,
+    ToggleTriangles,
+    ToggleGrid,
+    ToggleMaterials,
+    ToggleBoundary,
+    ToggleVectors,
+    ToggleStreamlines,
+    ToggleStatistics,
+    ResetView,
+    FitToMesh,
+    NextTimeStep,
+    PreviousTimeStep,
+    PlayPause,
+    ToggleEditMode,
+    Undo,
+    Redo,
+    OpenPalette,
+}
+
+impl Command {
+    pub const ALL: [Command; 20] = [
+        Command::ToggleHeatmap,
+        Command::ToggleContours,
+        Command::TogglePoints,
+        Command::ToggleNumbers,
+        Command::ToggleTriangles,
+        Command::ToggleGrid,
+        Command::ToggleMaterials,
+        Command::ToggleBoundary,
+        Command::ToggleVectors,
+        Command::ToggleStreamlines,
+        Command::ToggleStatistics,
+        Command::ResetView,
+        Command::FitToMesh,
+        Command::NextTimeStep,
+        Command::PreviousTimeStep,
+        Command::PlayPause,
+        Command::ToggleEditMode,
+        Command::Undo,
+        Command::Redo,
+        Command::OpenPalette,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::ToggleHeatmap => "Toggle heatmap",
+            Command::ToggleContours => "Toggle contours",
+            Command::TogglePoints => "Toggle points",
+            Command::ToggleNumbers => "Toggle point numbers",
+            Command::ToggleTriangles => "Toggle triangulation",
+            Command::ToggleGrid => "Toggle plot grid",
+            Command::ToggleMaterials => "Toggle materials",
+            Command::ToggleBoundary => "Toggle mesh boundary",
+            Command::ToggleVectors => "Toggle vector field",
+            Command::ToggleStreamlines => "Toggle streamlines",
+            Command::ToggleStatistics => "Toggle material statistics",
+            Command::ResetView => "Reset view",
+            Command::FitToMesh => "Fit to mesh",
+            Command::NextTimeStep => "Next time step",
+            Command::PreviousTimeStep => "Previous time step",
+            Command::PlayPause => "Play or pause",
+            Command::ToggleEditMode => "Toggle edit mode",
+            Command::Undo => "Undo edit",
+            Command::Redo => "Redo edit",
+            Command::OpenPalette => "Command palette",
+        }
+    }
+
+    fn default_shortcut(self) -> &'static str {
+        match self {
+            Command::ToggleHeatmap => "H",
+            Command::ToggleContours => "C",
+            Command::TogglePoints => "P",
+            Command::ToggleNumbers => "N",
+            Command::ToggleTriangles => "T",
+            Command::ToggleGrid => "G",
+            Command::ToggleMaterials => "M",
+            Command::ToggleBoundary => "B",
+            Command::ToggleVectors => "V",
+            Command::ToggleStreamlines => "S",
+            Command::ToggleStatistics => "I",
+            Command::ResetView => "R",
+            Command::FitToMesh => "F",
+            Command::NextTimeStep => "ArrowRight",
+            Command::PreviousTimeStep => "ArrowLeft",
+            Command::PlayPause => "Space",
+            Command::ToggleEditMode => "E",
+            Command::Undo => "Ctrl+Z",
+            Command::Redo => "Ctrl+Y",
+            Command::OpenPalette => "Ctrl+P",
+        }
+    }
+}
+
+pub fn default_bindings() -> BTreeMap<Command, String> {
+    Command::ALL
+        .iter()
+        .map(|&command| (command, command.default_shortcut().to_string()))
+        .collect()
+}
+
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+
+    for part in text.split('+').map(|part| part.trim()) {
+        match part.to_lowercase().as_str() {
+            "ctrl" => modifiers = modifiers | Modifiers::CTRL,
+            "shift" => modifiers = modifiers | Modifiers::SHIFT,
+            "alt" => modifiers = modifiers | Modifiers::ALT,
+            "cmd" | "command" => modifiers = modifiers | Modifiers::COMMAND,
+            _ if key.is_none() => key = Some(Key::from_name(part)?),
+            _ => return None,
+        }
+    }
+
+    Some(KeyboardShortcut::new(modifiers, key?))
+}
+
+pub fn matches(command: Command, query: &str) -> bool {
+    command
+        .name()
+        .to_lowercase()
+        .contains(&query.trim().to_lowercase())
+}
@@ -0,0 +1,93 @@
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+pub fn isoline_levels(minimum: f64, maximum: f64, count: u16) -> Vec<f64> {
+    let step = (maximum - minimum) / (count as f64);
+    (0..=count).map(|i| minimum + (i as f64) * step).collect()
+}
+
+pub fn isoline_segments(
+    points: &[(f64, f64)],
+    triangles: &[Vec<usize>],
+    values: &[f64],
+    level: f64,
+    visible: impl Fn(usize) -> bool,
+) -> Vec<([f64; 2], [f64; 2])> {
+    let mut isoline_segments: Vec<([f64; 2], [f64; 2])> = Vec::new();
+
+    for (index, triangle) in triangles.iter().enumerate() {
+        if !visible(index) {
+            continue;
+        }
+
+        let vertices: Vec<(Point, f64)> = triangle
+            .iter()
+            .take(3)
+            .map(|&idx| {
+                let point = Point {
+                    x: points[idx].0,
+                    y: points[idx].1,
+                };
+                let value = values[idx];
+                (point, value)
+            })
+            .collect();
+
+        let signs: Vec<bool> = vertices.iter().map(|(_, value)| *value >= level).collect();
+
+        if signs.iter().all(|&x| x) || signs.iter().all(|&x| !x) {
+            continue;
+        }
+
+        let mut intersections: Vec<[f64; 2]> = Vec::new();
+
+        for i in 0..3 {
+            let j = (i + 1) % 3;
+            if signs[i] != signs[j] {
+                let (p1, v1) = &vertices[i];
+                let (p2, v2) = &vertices[j];
+
+                let t = (level - v1) / (v2 - v1);
+                let intersection = [p1.x + t * (p2.x - p1.x), p1.y + t * (p2.y - p1.y)];
+
+                intersections.push(intersection);
+            }
+        }
+
+        if intersections.len() == 2 {
+            isoline_segments.push((intersections[0], intersections[1]));
+        }
+    }
+
+    connect_segments(&mut isoline_segments);
+    isoline_segments
+}
+
+pub fn connect_segments(segments: &mut Vec<([f64; 2], [f64; 2])>) {
+    let tolerance = 1e-10;
+    let mut i = 0;
+    while i < segments.len() {
+        let mut j = i + 1;
+        while j < segments.len() {
+            let end1 = segments[i].1;
+            let (start2, end2) = segments[j];
+
+            if distance(end1, start2) < tolerance {
+                segments[i].1 = end2;
+                segments.remove(j);
+            } else if distance(end1, end2) < tolerance {
+                segments[i].1 = start2;
+                segments.remove(j);
+            } else {
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+}
+
+fn distance(p1: [f64; 2], p2: [f64; 2]) -> f64 {
+    ((p2[0] - p1[0]).powi(2) + (p2[1] - p1[1]).powi(2)).sqrt()
+}
@@ -0,0 +1,158 @@
+use std::io;
+
+use crate::expression::Expression;
+use crate::field::SOLUTION_FIELD;
+use crate::mesh::{self, Mesh};
+use crate::transfer;
+
+pub struct ConvergenceLevel {
+    pub folder: String,
+    pub h: f64,
+    pub l2: f64,
+    pub maximum: f64,
+    pub missing: usize,
+}
+
+#[derive(Default)]
+pub struct ConvergenceStudy {
+    pub folders: String,
+    pub reference_expression: String,
+    pub levels: Vec<ConvergenceLevel>,
+    pub reference_folder: String,
+    pub status: String,
+}
+
+impl ConvergenceStudy {
+    pub fn folders(&self) -> Vec<String> {
+        self.folders
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    pub fn run(&mut self, load: impl Fn(&str) -> io::Result<Mesh>) {
+        self.levels.clear();
+
+        let mut folders = self.folders();
+        let analytic = !self.reference_expression.trim().is_empty();
+        let required = if analytic { 1 } else { 2 };
+        if folders.len() < required {
+            self.status = "List more grid folders, coarsest first".to_string();
+            return;
+        }
+
+        let (reference_folder, result) = if analytic {
+            let reference = match Expression::parse(&self.reference_expression) {
+                Ok(reference) => reference,
+                Err(e) => {
+                    self.status = format!("Invalid reference: {}", e);
+                    return;
+                }
+            };
+            let result = folders
+                .iter()
+                .map(|folder| {
+                    let level = load(folder)?;
+                    let exact = reference.evaluate_at_nodes(&level.points, 0.0);
+                    let exact: Vec<Option<f64>> = exact.into_iter().map(Some).collect();
+                    Ok(level_norms(folder, &level, &exact))
+                })
+                .collect::<io::Result<Vec<_>>>();
+            (self.reference_expression.clone(), result)
+        } else {
+            let reference_folder = folders.pop().unwrap();
+            let result = load(&reference_folder).and_then(|reference| {
+                folders
+                    .iter()
+                    .map(|folder| {
+                        let level = load(folder)?;
+                        let transferred = transfer::transfer(&reference, SOLUTION_FIELD, &level);
+                        Ok(level_norms(folder, &level, &transferred))
+                    })
+                    .collect::<io::Result<Vec<_>>>()
+            });
+            (reference_folder, result)
+        };
+
+        match result {
+            Ok(levels) => {
+                self.levels = levels;
+                self.reference_folder = reference_folder;
+                self.status = match self.fitted_order() {
+                    Some(order) => format!("Estimated order {:.3}", order),
+                    None => "Not enough levels to estimate the order".to_string(),
+                };
+            }
+            Err(e) => self.status = format!("Convergence study failed: {}", e),
+        }
+    }
+
+    pub fn orders(&self) -> Vec<Option<f64>> {
+        self.levels
+            .windows(2)
+            .map(|pair| order(pair[0].h, pair[0].l2, pair[1].h, pair[1].l2))
+            .collect()
+    }
+
+    pub fn fitted_order(&self) -> Option<f64> {
+        let samples: Vec<(f64, f64)> = self
+            .levels
+            .iter()
+            .filter(|level| level.h > 0.0 && level.l2 > 0.0)
+            .map(|level| (level.h.ln(), level.l2.ln()))
+            .collect();
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let count = samples.len() as f64;
+        let mean_x = samples.iter().map(|s| s.0).sum::<f64>() / count;
+        let mean_y = samples.iter().map(|s| s.1).sum::<f64>() / count;
+        let covariance: f64 = samples
+            .iter()
+            .map(|s| (s.0 - mean_x) * (s.1 - mean_y))
+            .sum();
+        let variance: f64 = samples.iter().map(|s| (s.0 - mean_x).powi(2)).sum();
+        (variance > 0.0).then(|| covariance / variance)
+    }
+}
+
+fn order(coarse_h: f64, coarse_error: f64, fine_h: f64, fine_error: f64) -> Option<f64> {
+    let ratio = (coarse_h / fine_h).ln();
+    (coarse_error > 0.0 && fine_error > 0.0 && ratio != 0.0)
+        .then(|| (coarse_error / fine_error).ln() / ratio)
+}
+
+fn level_norms(folder: &str, level: &Mesh, reference: &[Option<f64>]) -> ConvergenceLevel {
+    let (_, norms) =
+        transfer::difference_norms(level, &level.fields[SOLUTION_FIELD].values, reference);
+
+    ConvergenceLevel {
+        folder: folder.to_string(),
+        h: mesh_size(level),
+        l2: norms.l2,
+        maximum: norms.maximum,
+        missing: norms.missing,
+    }
+}
+
+pub fn mesh_size(mesh: &Mesh) -> f64 {
+    mesh.elements
+        .iter()
+        .flat_map(|element| {
+            let outline = mesh::element_outline(element);
+            let corners: Vec<(f64, f64)> = outline
+                .iter()
+                .step_by((outline.len() / 4).max(1))
+                .map(|&node| mesh.points[node])
+                .collect();
+            (0..corners.len()).map(move |k| {
+                let (ax, ay) = corners[k];
+                let (bx, by) = corners[(k + 1) % corners.len()];
+                (bx - ax).hypot(by - ay)
+            })
+        })
+        .fold(0.0, f64::max)
+}
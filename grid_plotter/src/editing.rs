@@ -0,0 +1,272 @@
+use crate::boundary::{self, BoundaryValue};
+use crate::materials;
+use crate::mesh::Mesh;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum EditTool {
+    MoveNode,
+    Material,
+    Dirichlet,
+    Neumann,
+}
+
+#[derive(Clone)]
+pub enum Edit {
+    MoveNode {
+        node: usize,
+        from: (f64, f64),
+        to: (f64, f64),
+    },
+    SetMaterial {
+        element: usize,
+        from: usize,
+        to: usize,
+    },
+    InsertDirichlet {
+        index: usize,
+        node: usize,
+        value: Option<BoundaryValue>,
+    },
+    RemoveDirichlet {
+        index: usize,
+        node: usize,
+        value: Option<BoundaryValue>,
+    },
+    InsertNeumann {
+        index: usize,
+        edge: Vec<usize>,
+        value: Option<BoundaryValue>,
+    },
+    RemoveNeumann {
+        index: usize,
+        edge: Vec<usize>,
+        value: Option<BoundaryValue>,
+    },
+}
+
+impl Edit {
+    pub fn toggle_dirichlet(mesh: &Mesh, node: usize) -> Self {
+        match mesh.dirichlet.iter().position(|&other| other == node) {
+            Some(index) => Edit::RemoveDirichlet {
+                index,
+                node,
+                value: mesh.conditions.dirichlet.get(&node).cloned(),
+            },
+            None => Edit::InsertDirichlet {
+                index: mesh.dirichlet.len(),
+                node,
+                value: None,
+            },
+        }
+    }
+
+    pub fn toggle_neumann(mesh: &Mesh, edge: &[usize]) -> Self {
+        let key = boundary::edge_key(edge);
+        match mesh
+            .neumann
+            .iter()
+            .position(|other| other.len() >= 2 && boundary::edge_key(other) == key)
+        {
+            Some(index) => Edit::RemoveNeumann {
+                index,
+                edge: mesh.neumann[index].clone(),
+                value: mesh.conditions.neumann.get(&key).cloned(),
+            },
+            None => Edit::InsertNeumann {
+                index: mesh.neumann.len(),
+                edge: edge.to_vec(),
+                value: None,
+            },
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Edit::MoveNode { node, from, to } => Edit::MoveNode {
+                node,
+                from: to,
+                to: from,
+            },
+            Edit::SetMaterial { element, from, to } => Edit::SetMaterial {
+                element,
+                from: to,
+                to: from,
+            },
+            Edit::InsertDirichlet { index, node, value } => {
+                Edit::RemoveDirichlet { index, node, value }
+            }
+            Edit::RemoveDirichlet { index, node, value } => {
+                Edit::InsertDirichlet { index, node, value }
+            }
+            Edit::InsertNeumann { index, edge, value } => {
+                Edit::RemoveNeumann { index, edge, value }
+            }
+            Edit::RemoveNeumann { index, edge, value } => {
+                Edit::InsertNeumann { index, edge, value }
+            }
+        }
+    }
+
+    pub fn apply(&self, mesh: &mut Mesh) {
+        match self {
+            Edit::MoveNode { node, to, .. } => mesh.points[*node] = *to,
+            Edit::SetMaterial { element, to, .. } => {
+                materials::set_element_material(&mut mesh.elements[*element], *to)
+            }
+            Edit::InsertDirichlet { index, node, value } => {
+                mesh.dirichlet.insert(*index, *node);
+                match value {
+                    Some(value) => mesh.conditions.dirichlet.insert(*node, value.clone()),
+                    None => mesh.conditions.dirichlet.remove(node),
+                };
+            }
+            Edit::RemoveDirichlet { index, node, .. } => {
+                mesh.dirichlet.remove(*index);
+                mesh.conditions.dirichlet.remove(node);
+            }
+            Edit::InsertNeumann { index, edge, value } => {
+                mesh.neumann.insert(*index, edge.clone());
+                let key = boundary::edge_key(edge);
+                match value {
+                    Some(value) => mesh.conditions.neumann.insert(key, value.clone()),
+                    None => mesh.conditions.neumann.remove(&key),
+                };
+            }
+            Edit::RemoveNeumann { index, edge, .. } => {
+                mesh.neumann.remove(*index);
+                mesh.conditions.neumann.remove(&boundary::edge_key(edge));
+            }
+        }
+
+        if !matches!(self, Edit::MoveNode { .. } | Edit::SetMaterial { .. }) {
+            mesh.update_uncovered_edges();
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl EditHistory {
+    pub fn apply(&mut self, mesh: &mut Mesh, edit: Edit) {
+        edit.apply(mesh);
+        self.record(edit);
+    }
+
+    pub fn record(&mut self, edit: Edit) {
+        self.undo.push(edit);
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, mesh: &mut Mesh) -> bool {
+        let Some(edit) = self.undo.pop() else {
+            return false;
+        };
+        edit.inverse().apply(mesh);
+        self.redo.push(edit);
+        true
+    }
+
+    pub fn redo(&mut self, mesh: &mut Mesh) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        edit.apply(mesh);
+        self.undo.push(edit);
+        true
+    }
+
+    pub fn undo_count(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_count(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+pub fn nearest_node(points: &[(f64, f64)], p: [f64; 2], radius: f64) -> Option<usize> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(index, &(x, y))| (index, (x - p[0]).hypot(y - p[1])))
+        .filter(|&(_, distance)| distance <= radius)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+pub fn nearest_edge<'a>(
+    points: &[(f64, f64)],
+    edges: &'a [Vec<usize>],
+    p: [f64; 2],
+    radius: f64,
+) -> Option<&'a [usize]> {
+    edges
+        .iter()
+        .map(|edge| {
+            let distance = edge
+                .windows(2)
+                .map(|pair| segment_distance(points[pair[0]], points[pair[1]], p))
+                .fold(f64::INFINITY, f64::min);
+            (edge, distance)
+        })
+        .filter(|&(_, distance)| distance <= radius)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(edge, _)| edge.as_slice())
+}
+
+fn segment_distance(a: (f64, f64), b: (f64, f64), p: [f64; 2]) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (((p[0] - a.0) * dx + (p[1] - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (a.0 + t * dx - p[0]).hypot(a.1 + t * dy - p[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loaders;
+
+    #[test]
+    fn boundary_toggles_keep_conditions_in_sync() {
+        let mut mesh =
+            loaders::load_mesh(&format!("{}/grid/test_grid", env!("CARGO_MANIFEST_DIR"))).unwrap();
+        let node = mesh.dirichlet[0];
+        let edge = mesh.neumann[0].clone();
+        let value = BoundaryValue {
+            type_id: 2,
+            values: vec![0.5],
+        };
+        mesh.conditions.dirichlet.insert(node, value.clone());
+        mesh.conditions
+            .neumann
+            .insert(boundary::edge_key(&edge), value.clone());
+        let conditions = mesh.conditions.clone();
+
+        let mut history = EditHistory::default();
+        let edit = Edit::toggle_dirichlet(&mesh, node);
+        history.apply(&mut mesh, edit);
+        let edit = Edit::toggle_neumann(&mesh, &edge);
+        history.apply(&mut mesh, edit);
+        assert!(mesh.conditions.dirichlet.is_empty());
+        assert!(mesh.conditions.neumann.is_empty());
+
+        assert!(history.undo(&mut mesh) && history.undo(&mut mesh));
+        assert!(mesh.conditions == conditions);
+
+        assert!(history.redo(&mut mesh));
+        assert!(mesh.conditions.dirichlet.is_empty());
+        mesh.conditions.dirichlet.insert(node, value);
+        let edit = Edit::toggle_dirichlet(&mesh, node);
+        history.apply(&mut mesh, edit);
+        assert!(mesh.dirichlet.contains(&node));
+        assert!(!mesh.conditions.dirichlet.contains_key(&node));
+    }
+}
@@ -0,0 +1,312 @@
+use std::f64::consts;
+
+#[derive(Clone, Copy)]
+pub struct Variables {
+    pub x: f64,
+    pub y: f64,
+    pub t: f64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Variable {
+    X,
+    Y,
+    T,
+}
+
+type Function = fn(f64) -> f64;
+
+#[derive(Clone)]
+pub enum Expression {
+    Number(f64),
+    Variable(Variable),
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Function(Function, Box<Expression>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    LeftParen,
+    RightParen,
+}
+
+const FUNCTIONS: [(&str, Function); 17] = [
+    ("sin", f64::sin),
+    ("cos", f64::cos),
+    ("tan", f64::tan),
+    ("asin", f64::asin),
+    ("acos", f64::acos),
+    ("atan", f64::atan),
+    ("sinh", f64::sinh),
+    ("cosh", f64::cosh),
+    ("tanh", f64::tanh),
+    ("exp", f64::exp),
+    ("ln", f64::ln),
+    ("log", f64::log10),
+    ("sqrt", f64::sqrt),
+    ("abs", f64::abs),
+    ("floor", f64::floor),
+    ("ceil", f64::ceil),
+    ("sign", f64::signum),
+];
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let expression = parser.sum()?;
+        match parser.peek() {
+            None => Ok(expression),
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+        }
+    }
+
+    pub fn evaluate(&self, variables: Variables) -> f64 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Variable(Variable::X) => variables.x,
+            Expression::Variable(Variable::Y) => variables.y,
+            Expression::Variable(Variable::T) => variables.t,
+            Expression::Negate(operand) => -operand.evaluate(variables),
+            Expression::Binary(operator, left, right) => {
+                let left = left.evaluate(variables);
+                let right = right.evaluate(variables);
+                match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    _ => left.powf(right),
+                }
+            }
+            Expression::Function(function, argument) => function(argument.evaluate(variables)),
+        }
+    }
+
+    pub fn evaluate_at_nodes(&self, points: &[(f64, f64)], t: f64) -> Vec<f64> {
+        points
+            .iter()
+            .map(|&(x, y)| self.evaluate(Variables { x, y, t }))
+            .collect()
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek() {
+                let exponent_sign = (c == '+' || c == '-') && number.ends_with(['e', 'E']);
+                if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                    number.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let value = number
+                .parse()
+                .map_err(|_| format!("Invalid number {}", number))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut identifier = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' || c == '.' {
+                    identifier.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let identifier = identifier.to_lowercase();
+            let identifier = identifier.strip_prefix("math.").unwrap_or(&identifier);
+            let identifier = identifier.strip_prefix("p.").unwrap_or(identifier);
+            tokens.push(Token::Identifier(identifier.to_string()));
+        } else {
+            chars.next();
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '^' => Token::Operator(c),
+                '(' => Token::LeftParen,
+                ')' => Token::RightParen,
+                _ => return Err(format!("Unexpected character {}", c)),
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        let mut left = self.product()?;
+        while let Some(&Token::Operator(operator @ ('+' | '-'))) = self.peek() {
+            self.next();
+            let right = self.product()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        let mut left = self.unary()?;
+        while let Some(&Token::Operator(operator @ ('*' | '/'))) = self.peek() {
+            self.next();
+            let right = self.unary()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some(Token::Operator('-')) => {
+                self.next();
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Operator('+')) => {
+                self.next();
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expression, String> {
+        let base = self.primary()?;
+        if let Some(Token::Operator('^')) = self.peek() {
+            self.next();
+            let exponent = self.unary()?;
+            return Ok(Expression::Binary('^', Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::LeftParen) => {
+                let expression = self.sum()?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(expression),
+                    _ => Err("Missing closing parenthesis".to_string()),
+                }
+            }
+            Some(Token::Identifier(name)) => match name.as_str() {
+                "x" => Ok(Expression::Variable(Variable::X)),
+                "y" => Ok(Expression::Variable(Variable::Y)),
+                "t" => Ok(Expression::Variable(Variable::T)),
+                "pi" => Ok(Expression::Number(consts::PI)),
+                "e" => Ok(Expression::Number(consts::E)),
+                _ => {
+                    let function = FUNCTIONS
+                        .iter()
+                        .find(|(function, _)| *function == name)
+                        .map(|&(_, function)| function)
+                        .ok_or_else(|| format!("Unknown name {}", name))?;
+                    if self.next() != Some(Token::LeftParen) {
+                        return Err(format!("Expected ( after {}", name));
+                    }
+                    let argument = self.sum()?;
+                    if self.next() != Some(Token::RightParen) {
+                        return Err("Missing closing parenthesis".to_string());
+                    }
+                    Ok(Expression::Function(function, Box::new(argument)))
+                }
+            },
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(text: &str) -> f64 {
+        Expression::parse(text).unwrap().evaluate(Variables {
+            x: 2.0,
+            y: 3.0,
+            t: 0.5,
+        })
+    }
+
+    #[test]
+    fn operators_follow_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3"), 9.0);
+        assert_eq!(evaluate("8 / 4 / 2"), 1.0);
+        assert_eq!(evaluate("10 - 4 - 3"), 3.0);
+        assert_eq!(evaluate("2 * 3 ^ 2"), 18.0);
+        assert_eq!(evaluate("x * y + t"), 6.5);
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(evaluate("(2 ^ 3) ^ 2"), 64.0);
+        assert_eq!(evaluate("2 ^ -1"), 0.5);
+    }
+
+    #[test]
+    fn unary_minus_binds_looser_than_power() {
+        assert_eq!(evaluate("-2 ^ 2"), -4.0);
+        assert_eq!(evaluate("(-2) ^ 2"), 4.0);
+        assert_eq!(evaluate("--x"), 2.0);
+        assert_eq!(evaluate("3 * -y"), -9.0);
+        assert_eq!(evaluate("+x - -1"), 3.0);
+    }
+
+    #[test]
+    fn numbers_accept_exponents() {
+        assert_eq!(evaluate("1e-3"), 1e-3);
+        assert_eq!(evaluate("2.5E+2"), 250.0);
+        assert_eq!(evaluate(".5 + 1e2"), 100.5);
+        assert_eq!(evaluate("1e-3 * x"), 2e-3);
+    }
+
+    #[test]
+    fn names_and_functions() {
+        assert_eq!(evaluate("sqrt(x * 8)"), 4.0);
+        assert_eq!(evaluate("Math.abs(-y)"), 3.0);
+        assert_eq!(evaluate("cos(0) + ln(e)"), 2.0);
+        assert!((evaluate("sin(pi / 2)") - 1.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        for text in [
+            "", "1 +", "(1 + 2", "1 + 2)", "2 3", "sin 1", "sin(1", "foo(1)", "z", "1 # 2", "1e",
+            "1..2", "* 2",
+        ] {
+            assert!(Expression::parse(text).is_err(), "{:?} was accepted", text);
+        }
+    }
+}
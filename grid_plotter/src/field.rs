@@ -0,0 +1,52 @@
+use crate::mesh;
+
+pub const SOLUTION_FIELD: usize = 0;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum FieldLocation {
+    Node,
+    Element,
+}
+
+pub struct Field {
+    pub name: String,
+    pub source: String,
+    pub location: FieldLocation,
+    pub values: Vec<f64>,
+}
+
+impl Field {
+    pub fn new(name: &str, source: &str, values: Vec<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            source: source.to_string(),
+            location: FieldLocation::Node,
+            values,
+        }
+    }
+
+    pub fn range(&self) -> (f64, f64) {
+        self.values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            })
+    }
+
+    pub fn nodal_average(&self, elements: &[Vec<usize>], points_count: usize) -> Vec<f64> {
+        let mut sums = vec![0.0; points_count];
+        let mut counts = vec![0usize; points_count];
+
+        for (element, &value) in elements.iter().zip(&self.values) {
+            for node in mesh::element_nodes(element) {
+                sums[node] += value;
+                counts[node] += 1;
+            }
+        }
+
+        sums.iter()
+            .zip(&counts)
+            .map(|(&sum, &count)| if count > 0 { sum / count as f64 } else { 0.0 })
+            .collect()
+    }
+}
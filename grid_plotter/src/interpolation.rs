@@ -0,0 +1,67 @@
+pub fn barycentric(points: &[(f64, f64)], triangle: &[usize], p: [f64; 2]) -> [f64; 3] {
+    let (x1, y1) = points[triangle[0]];
+    let (x2, y2) = points[triangle[1]];
+    let (x3, y3) = points[triangle[2]];
+
+    let det = (x2 - x1) * (y3 - y1) - (x3 - x1) * (y2 - y1);
+    let l2 = ((p[0] - x1) * (y3 - y1) - (x3 - x1) * (p[1] - y1)) / det;
+    let l3 = ((x2 - x1) * (p[1] - y1) - (p[0] - x1) * (y2 - y1)) / det;
+
+    [1.0 - l2 - l3, l2, l3]
+}
+
+pub fn contains(coordinates: [f64; 3]) -> bool {
+    let tolerance = 1e-12;
+    coordinates.iter().all(|&l| l >= -tolerance)
+}
+
+pub fn locate_triangle(
+    points: &[(f64, f64)],
+    triangles: &[Vec<usize>],
+    p: [f64; 2],
+) -> Option<(usize, [f64; 3])> {
+    triangles.iter().enumerate().find_map(|(i, triangle)| {
+        let coordinates = barycentric(points, triangle, p);
+        contains(coordinates).then_some((i, coordinates))
+    })
+}
+
+pub fn triangle_gradient(points: &[(f64, f64)], values: &[f64], triangle: &[usize]) -> [f64; 2] {
+    let (x1, y1) = points[triangle[0]];
+    let (x2, y2) = points[triangle[1]];
+    let (x3, y3) = points[triangle[2]];
+    let (u1, u2, u3) = (
+        values[triangle[0]],
+        values[triangle[1]],
+        values[triangle[2]],
+    );
+
+    let det = (x2 - x1) * (y3 - y1) - (x3 - x1) * (y2 - y1);
+    let dudx = ((u2 - u1) * (y3 - y1) - (u3 - u1) * (y2 - y1)) / det;
+    let dudy = ((x2 - x1) * (u3 - u1) - (x3 - x1) * (u2 - u1)) / det;
+
+    [dudx, dudy]
+}
+
+pub fn triangle_area(points: &[(f64, f64)], triangle: &[usize]) -> f64 {
+    let (x1, y1) = points[triangle[0]];
+    let (x2, y2) = points[triangle[1]];
+    let (x3, y3) = points[triangle[2]];
+
+    ((x2 - x1) * (y3 - y1) - (x3 - x1) * (y2 - y1)).abs() / 2.0
+}
+
+pub fn bounding_box(points: &[(f64, f64)]) -> ([f64; 2], [f64; 2]) {
+    points.iter().fold(
+        (
+            [f64::INFINITY, f64::INFINITY],
+            [f64::NEG_INFINITY, f64::NEG_INFINITY],
+        ),
+        |(min, max), &(x, y)| {
+            (
+                [min[0].min(x), min[1].min(y)],
+                [max[0].max(x), max[1].max(y)],
+            )
+        },
+    )
+}
@@ -0,0 +1,145 @@
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum LayerKind {
+    Materials,
+    Heatmap,
+    Bands,
+    Outline,
+    Contours,
+    Vectors,
+    Streamlines,
+    Boundary,
+    UncoveredBoundary,
+    Nodes,
+    Labels,
+    ElementLabels,
+    LocalNumbers,
+    Orientation,
+    Dirichlet,
+    Neumann,
+    Robin,
+    BoundaryTypes,
+}
+
+impl LayerKind {
+    pub const ALL: [LayerKind; 18] = [
+        LayerKind::Materials,
+        LayerKind::Heatmap,
+        LayerKind::Bands,
+        LayerKind::Outline,
+        LayerKind::Contours,
+        LayerKind::Vectors,
+        LayerKind::Streamlines,
+        LayerKind::Boundary,
+        LayerKind::UncoveredBoundary,
+        LayerKind::Nodes,
+        LayerKind::Labels,
+        LayerKind::ElementLabels,
+        LayerKind::LocalNumbers,
+        LayerKind::Orientation,
+        LayerKind::Dirichlet,
+        LayerKind::Neumann,
+        LayerKind::Robin,
+        LayerKind::BoundaryTypes,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LayerKind::Materials => "Materials",
+            LayerKind::Heatmap => "Heatmap",
+            LayerKind::Bands => "Bands",
+            LayerKind::Outline => "Mesh outline",
+            LayerKind::Contours => "Contours",
+            LayerKind::Vectors => "Vector field",
+            LayerKind::Streamlines => "Streamlines",
+            LayerKind::Boundary => "Mesh boundary",
+            LayerKind::UncoveredBoundary => "Uncovered boundary",
+            LayerKind::Nodes => "Nodes",
+            LayerKind::Labels => "Node labels",
+            LayerKind::ElementLabels => "Element labels",
+            LayerKind::LocalNumbers => "Local node numbers",
+            LayerKind::Orientation => "Element orientation",
+            LayerKind::Dirichlet => "Dirichlet nodes",
+            LayerKind::Neumann => "Neumann edges",
+            LayerKind::Robin => "Robin edges",
+            LayerKind::BoundaryTypes => "Boundary types",
+        }
+    }
+
+    pub fn size_label(self) -> &'static str {
+        match self {
+            LayerKind::Materials | LayerKind::Heatmap | LayerKind::Bands => "Edge width",
+            LayerKind::Nodes | LayerKind::Dirichlet => "Radius",
+            LayerKind::Labels | LayerKind::ElementLabels | LayerKind::LocalNumbers => "Font size",
+            _ => "Line width",
+        }
+    }
+
+    fn default_layer(self) -> Layer {
+        let (visible, color, size) = match self {
+            LayerKind::Materials => (false, [96, 96, 96], 1.0),
+            LayerKind::Heatmap => (true, [96, 96, 96], 1.0),
+            LayerKind::Bands => (false, [96, 96, 96], 1.0),
+            LayerKind::Outline => (true, [96, 96, 96], 1.0),
+            LayerKind::Contours => (true, [0, 100, 0], 2.0),
+            LayerKind::Vectors => (false, [0, 0, 0], 1.0),
+            LayerKind::Streamlines => (true, [255, 255, 255], 1.5),
+            LayerKind::Boundary => (false, [0, 0, 0], 3.0),
+            LayerKind::UncoveredBoundary => (true, [255, 0, 255], 4.0),
+            LayerKind::Nodes => (false, [0, 0, 0], 5.0),
+            LayerKind::Labels => (false, [0, 0, 139], 15.0),
+            LayerKind::ElementLabels => (false, [139, 0, 0], 14.0),
+            LayerKind::LocalNumbers => (false, [0, 90, 160], 11.0),
+            LayerKind::Orientation => (false, [0, 120, 0], 1.5),
+            LayerKind::Dirichlet => (false, [255, 165, 0], 5.0),
+            LayerKind::Neumann => (false, [255, 0, 0], 2.0),
+            LayerKind::Robin => (false, [0, 160, 160], 2.0),
+            LayerKind::BoundaryTypes => (false, [0, 0, 0], 3.0),
+        };
+
+        Layer {
+            kind: self,
+            visible,
+            opacity: 1.0,
+            color,
+            size,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct Layer {
+    pub kind: LayerKind,
+    pub visible: bool,
+    pub opacity: f32,
+    pub color: [u8; 3],
+    pub size: f32,
+}
+
+impl Layer {
+    pub fn color32(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        self.tint(Color32::from_rgb(r, g, b))
+    }
+
+    pub fn tint(&self, color: Color32) -> Color32 {
+        color.gamma_multiply(self.opacity)
+    }
+}
+
+pub fn default_layers() -> Vec<Layer> {
+    LayerKind::ALL
+        .iter()
+        .map(|kind| kind.default_layer())
+        .collect()
+}
+
+pub fn complete_layers(layers: &mut Vec<Layer>) {
+    for kind in LayerKind::ALL {
+        if !layers.iter().any(|layer| layer.kind == kind) {
+            layers.push(kind.default_layer());
+        }
+    }
+}
@@ -0,0 +1,26 @@
+pub mod boundary;
+pub mod color;
+pub mod commands;
+pub mod contour;
+pub mod convergence;
+pub mod editing;
+pub mod expression;
+pub mod field;
+pub mod interpolation;
+pub mod layers;
+pub mod loaders;
+pub mod materials;
+pub mod mesh;
+pub mod plotter;
+pub mod refinement;
+pub mod settings;
+pub mod sparsity;
+pub mod statistics;
+pub mod streamlines;
+pub mod symmetry;
+pub mod timeline;
+pub mod transfer;
+
+pub use field::{Field, FieldLocation};
+pub use mesh::Mesh;
+pub use plotter::GridPlotter;
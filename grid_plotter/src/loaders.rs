@@ -0,0 +1,650 @@
+use serde::Deserialize;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::boundary::{self, BoundaryValue, ConditionKind, Conditions, EdgeValues};
+use crate::field::{Field, SOLUTION_FIELD};
+use crate::materials::{self, MaterialProperties};
+use crate::mesh::Mesh;
+use crate::timeline::Timeline;
+
+fn invalid_data(error: impl Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+fn parse_numbers<T: FromStr>(line: &str) -> io::Result<Vec<T>>
+where
+    T::Err: Display,
+{
+    line.split_whitespace()
+        .map(|number| number.parse().map_err(invalid_data))
+        .collect()
+}
+
+fn check_solution(solution: &[f64], points_count: usize) -> io::Result<()> {
+    if solution.len() == points_count {
+        Ok(())
+    } else {
+        Err(invalid_data(format!(
+            "the solution has {} values but the mesh has {} nodes",
+            solution.len(),
+            points_count
+        )))
+    }
+}
+
+fn check_nodes(
+    points_count: usize,
+    elements: &[Vec<usize>],
+    dirichlet: &[usize],
+    neumann: &[Vec<usize>],
+    robin: &[Vec<usize>],
+) -> io::Result<()> {
+    let missing = |owner: String, node: usize| {
+        invalid_data(format!(
+            "{} references node {}, but the mesh has {} nodes",
+            owner, node, points_count
+        ))
+    };
+    for (index, element) in elements.iter().enumerate() {
+        let material = materials::material_entry(element);
+        for (entry, &node) in element.iter().enumerate() {
+            if Some(entry) != material && node >= points_count {
+                return Err(missing(format!("element {}", index), node));
+            }
+        }
+    }
+    if let Some(&node) = dirichlet.iter().find(|&&node| node >= points_count) {
+        return Err(missing("dirichlet".to_string(), node));
+    }
+    for (name, edges) in [("neumann", neumann), ("robin", robin)] {
+        for (index, edge) in edges.iter().enumerate() {
+            if let Some(&node) = edge.iter().find(|&&node| node >= points_count) {
+                return Err(missing(format!("{} edge {}", name, index), node));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn read_mesh_from_file(filename: &str) -> io::Result<Vec<(f64, f64)>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    reader
+        .lines()
+        .enumerate()
+        .map(|(index, line)| match parse_numbers::<f64>(&line?)?[..] {
+            [x, y, ..] => Ok((x, y)),
+            _ => Err(invalid_data(format!(
+                "line {} has fewer than two coordinates",
+                index + 1
+            ))),
+        })
+        .collect()
+}
+
+pub fn read_elements_from_file(filename: &str) -> io::Result<Vec<Vec<usize>>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    reader.lines().map(|line| parse_numbers(&line?)).collect()
+}
+
+pub fn read_dirichlet_from_file(filename: &str) -> io::Result<Vec<usize>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    reader
+        .lines()
+        .map(|line| line?.trim().parse().map_err(invalid_data))
+        .collect()
+}
+
+pub fn read_neumann_from_file(filename: &str) -> io::Result<Vec<Vec<usize>>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    reader.lines().map(|line| parse_numbers(&line?)).collect()
+}
+
+pub fn read_solution_from_file(filename: &str) -> io::Result<Vec<f64>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    reader
+        .lines()
+        .map(|line| line?.trim().parse().map_err(invalid_data))
+        .collect()
+}
+
+pub fn read_boundary_values_from_file(filename: &str) -> io::Result<Vec<BoundaryValue>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    reader
+        .lines()
+        .map(|line| {
+            let line = line?;
+            let mut numbers = line.split_whitespace();
+            let type_id = match numbers.next() {
+                Some(number) => number.parse().map_err(invalid_data)?,
+                None => 0,
+            };
+            let values = numbers
+                .map(|number| number.parse().map_err(invalid_data))
+                .collect::<io::Result<_>>()?;
+            Ok(BoundaryValue { type_id, values })
+        })
+        .collect()
+}
+
+pub fn read_solution_series_from_file(filename: &str) -> io::Result<(Vec<f64>, Vec<Vec<f64>>)> {
+    let file = File::open(filename)?;
+    let mut lines = io::BufReader::new(file).lines();
+    let times: Vec<f64> = match lines.next() {
+        Some(line) => parse_numbers(&line?)?,
+        None => return Err(invalid_data("the solution series is empty")),
+    };
+    let mut layers = vec![Vec::new(); times.len()];
+    for line in lines {
+        let values: Vec<f64> = parse_numbers(&line?)?;
+        if values.is_empty() {
+            continue;
+        }
+        if values.len() != times.len() {
+            return Err(invalid_data(format!(
+                "expected {} values per line, found {}",
+                times.len(),
+                values.len()
+            )));
+        }
+        for (layer, value) in layers.iter_mut().zip(values) {
+            layer.push(value);
+        }
+    }
+    Ok((times, layers))
+}
+
+fn series_time(path: &Path) -> Option<f64> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("solution_"))
+        .and_then(|time| time.parse::<f64>().ok())
+        .filter(|time| time.is_finite())
+}
+
+pub fn read_solution_series_from_folder(folder: &str) -> io::Result<(Vec<f64>, Vec<Vec<f64>>)> {
+    let mut series: Vec<(f64, Vec<f64>)> = Vec::new();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if let Some(time) = series_time(&path) {
+            series.push((time, read_solution_from_file(&path.to_string_lossy())?));
+        }
+    }
+    series.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(series.into_iter().unzip())
+}
+
+pub fn read_fields_from_folder(folder: &str) -> io::Result<Vec<Field>> {
+    let mut fields: Vec<Field> = Vec::new();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.is_file() {
+            let name = path.file_name().unwrap().to_string_lossy();
+            let source = path.to_string_lossy();
+            let values = read_solution_from_file(&source)?;
+            fields.push(Field::new(&name, &source, values));
+        }
+    }
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fields)
+}
+
+pub fn read_fields_from_file(filename: &str) -> io::Result<Vec<Field>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    let mut fields: Vec<Field> = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('#') {
+            fields.push(Field::new(name.trim(), filename, Vec::new()));
+        } else if !line.is_empty() {
+            if let Some(field) = fields.last_mut() {
+                field.values.push(line.parse().map_err(invalid_data)?);
+            }
+        }
+    }
+    Ok(fields)
+}
+
+fn write_lines(filename: &Path, lines: impl Iterator<Item = String>) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(File::create(filename)?);
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
+
+fn join(values: &[usize]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn write_mesh_to_file(filename: &Path, points: &[(f64, f64)]) -> io::Result<()> {
+    write_lines(filename, points.iter().map(|(x, y)| format!("{} {}", x, y)))
+}
+
+pub fn write_elements_to_file(filename: &Path, elements: &[Vec<usize>]) -> io::Result<()> {
+    write_lines(filename, elements.iter().map(|element| join(element)))
+}
+
+pub fn write_dirichlet_to_file(filename: &Path, dirichlet: &[usize]) -> io::Result<()> {
+    write_lines(filename, dirichlet.iter().map(|node| node.to_string()))
+}
+
+pub fn write_neumann_to_file(filename: &Path, neumann: &[Vec<usize>]) -> io::Result<()> {
+    write_lines(filename, neumann.iter().map(|edge| join(edge)))
+}
+
+pub fn write_solution_to_file(filename: &Path, solution: &[f64]) -> io::Result<()> {
+    write_lines(filename, solution.iter().map(|value| value.to_string()))
+}
+
+pub fn write_boundary_values_to_file(filename: &Path, values: &[BoundaryValue]) -> io::Result<()> {
+    write_lines(
+        filename,
+        values.iter().map(|value| {
+            std::iter::once(value.type_id.to_string())
+                .chain(value.values.iter().map(|number| number.to_string()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+    )
+}
+
+pub fn save_mesh(mesh: &Mesh, folder: &str) -> io::Result<()> {
+    if folder.trim().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no folder to save to",
+        ));
+    }
+    let folder = Path::new(folder);
+    let path = |name: &str| folder.join(name);
+    let context = |name: &str| {
+        let path = path(name);
+        move |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+    };
+
+    std::fs::create_dir_all(folder)?;
+    write_mesh_to_file(&path("points"), &mesh.points).map_err(context("points"))?;
+    write_elements_to_file(&path("finite_elements"), &mesh.elements)
+        .map_err(context("finite_elements"))?;
+    write_dirichlet_to_file(&path("dirichlet"), &mesh.dirichlet).map_err(context("dirichlet"))?;
+    write_neumann_to_file(&path("neumann"), &mesh.neumann).map_err(context("neumann"))?;
+    write_solution_to_file(&path("solution"), &mesh.fields[SOLUTION_FIELD].values)
+        .map_err(context("solution"))?;
+    if !mesh.robin.is_empty() {
+        write_neumann_to_file(&path("robin"), &mesh.robin).map_err(context("robin"))?;
+    }
+
+    let edge_values = |kind: ConditionKind, edges: &[Vec<usize>]| -> Vec<BoundaryValue> {
+        edges
+            .iter()
+            .map(|edge| match edge.len() {
+                0 | 1 => BoundaryValue::default(),
+                _ => mesh.conditions.get(kind, edge).cloned().unwrap_or_default(),
+            })
+            .collect()
+    };
+    if !mesh.conditions.dirichlet.is_empty() {
+        let values: Vec<BoundaryValue> = mesh
+            .dirichlet
+            .iter()
+            .map(|node| {
+                mesh.conditions
+                    .dirichlet
+                    .get(node)
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect();
+        write_boundary_values_to_file(&path("dirichlet_values"), &values)
+            .map_err(context("dirichlet_values"))?;
+    }
+    if !mesh.conditions.neumann.is_empty() {
+        let values = edge_values(ConditionKind::Neumann, &mesh.neumann);
+        write_boundary_values_to_file(&path("neumann_values"), &values)
+            .map_err(context("neumann_values"))?;
+    }
+    if !mesh.conditions.robin.is_empty() {
+        let values = edge_values(ConditionKind::Robin, &mesh.robin);
+        write_boundary_values_to_file(&path("robin_values"), &values)
+            .map_err(context("robin_values"))?;
+    }
+
+    let fields = &mesh.fields[SOLUTION_FIELD + 1..];
+    if !fields.is_empty() {
+        let fields_folder = path("fields");
+        std::fs::create_dir_all(&fields_folder).map_err(context("fields"))?;
+        for field in fields {
+            let filename = fields_folder.join(&field.name);
+            write_solution_to_file(&filename, &field.values)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", filename.display(), e)))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_solution_series_to_file(
+    filename: &Path,
+    times: &[f64],
+    layers: &[Vec<f64>],
+) -> io::Result<()> {
+    let nodes_count = layers.first().map_or(0, |layer| layer.len());
+    let header = times
+        .iter()
+        .map(|time| time.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let rows = (0..nodes_count).map(|node| {
+        layers
+            .iter()
+            .map(|layer| layer[node].to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+    write_lines(filename, std::iter::once(header).chain(rows))
+}
+
+pub fn save_timeline(timeline: &Timeline, folder: &str) -> io::Result<()> {
+    let folder = Path::new(folder);
+    let solutions = folder.join("solutions");
+    let context = |path: &Path| {
+        let path = path.to_path_buf();
+        move |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+    };
+
+    if !solutions.is_dir() {
+        let filename = folder.join("solution_series");
+        return write_solution_series_to_file(&filename, &timeline.times, &timeline.layers)
+            .map_err(context(&filename));
+    }
+
+    for entry in std::fs::read_dir(&solutions).map_err(context(&solutions))? {
+        let path = entry?.path();
+        if series_time(&path).is_some() {
+            std::fs::remove_file(&path).map_err(context(&path))?;
+        }
+    }
+    for (time, layer) in timeline.times.iter().zip(&timeline.layers) {
+        let filename = solutions.join(format!("solution_{}", time));
+        write_solution_to_file(&filename, layer).map_err(context(&filename))?;
+    }
+    Ok(())
+}
+
+pub fn load_timeline(folder: &Path, nodes_count: usize) -> io::Result<Option<Timeline>> {
+    let series = if folder.join("solutions").is_dir() {
+        read_solution_series_from_folder(&folder.join("solutions").to_string_lossy())?
+    } else if folder.join("solution_series").is_file() {
+        read_solution_series_from_file(&folder.join("solution_series").to_string_lossy())?
+    } else {
+        return Ok(None);
+    };
+    let (times, layers) = series;
+    let timeline = Timeline::new(times, layers);
+    timeline.validate(nodes_count).map_err(invalid_data)?;
+    Ok(Some(timeline))
+}
+
+pub fn load_materials(folder: &Path) -> io::Result<Vec<MaterialProperties>> {
+    let filename = folder.join("materials.json");
+    if filename.is_file() {
+        read_materials_from_file(&filename.to_string_lossy())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+pub fn read_materials_from_file(filename: &str) -> io::Result<Vec<MaterialProperties>> {
+    let file = File::open(filename)?;
+    let reader = io::BufReader::new(file);
+    serde_json::from_reader(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub trait MeshReader {
+    fn name(&self) -> &str;
+    fn detect(&self, path: &Path) -> bool;
+    fn read(&self, path: &Path) -> io::Result<Mesh>;
+}
+
+pub struct NativeReader;
+
+impl MeshReader for NativeReader {
+    fn name(&self) -> &str {
+        "GridBuilder folder"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        path.is_dir() && path.join("points").is_file() && path.join("finite_elements").is_file()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Mesh> {
+        load_mesh(&path.to_string_lossy())
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonField {
+    name: String,
+    values: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct JsonMesh {
+    points: Vec<[f64; 2]>,
+    elements: Vec<Vec<usize>>,
+    #[serde(default)]
+    dirichlet: Vec<usize>,
+    #[serde(default)]
+    neumann: Vec<Vec<usize>>,
+    #[serde(default)]
+    fields: Vec<JsonField>,
+}
+
+pub struct JsonReader;
+
+impl MeshReader for JsonReader {
+    fn name(&self) -> &str {
+        "JSON mesh"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        if !path.is_file() {
+            return false;
+        }
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            return true;
+        }
+        let Ok(file) = File::open(path) else {
+            return false;
+        };
+        let mut head = Vec::new();
+        if file.take(4096).read_to_end(&mut head).is_err() {
+            return false;
+        }
+        let head = String::from_utf8_lossy(&head);
+        head.trim_start().starts_with('{') && head.contains("\"points\"")
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Mesh> {
+        let reader = io::BufReader::new(File::open(path)?);
+        let mesh: JsonMesh = serde_json::from_reader(reader)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let source = path.to_string_lossy();
+        let mut fields: Vec<Field> = mesh
+            .fields
+            .into_iter()
+            .map(|field| Field::new(&field.name, &source, field.values))
+            .collect();
+        let points_count = mesh.points.len();
+        match fields.iter().position(|field| field.name == "solution") {
+            Some(index) => fields.swap(SOLUTION_FIELD, index),
+            None => fields.insert(
+                SOLUTION_FIELD,
+                Field::new("solution", &source, vec![0.0; points_count]),
+            ),
+        }
+        check_solution(&fields[SOLUTION_FIELD].values, points_count)?;
+        check_nodes(
+            points_count,
+            &mesh.elements,
+            &mesh.dirichlet,
+            &mesh.neumann,
+            &[],
+        )?;
+
+        let points = mesh.points.iter().map(|p| (p[0], p[1])).collect();
+        Ok(Mesh::new(
+            points,
+            mesh.elements,
+            mesh.dirichlet,
+            mesh.neumann,
+            fields,
+        ))
+    }
+}
+
+pub struct ReaderRegistry {
+    readers: Vec<Box<dyn MeshReader>>,
+}
+
+impl Default for ReaderRegistry {
+    fn default() -> Self {
+        Self {
+            readers: vec![Box::new(NativeReader), Box::new(JsonReader)],
+        }
+    }
+}
+
+impl ReaderRegistry {
+    pub fn register(&mut self, reader: Box<dyn MeshReader>) {
+        self.readers.push(reader);
+    }
+
+    pub fn detect(&self, path: &Path) -> Option<&dyn MeshReader> {
+        self.readers
+            .iter()
+            .find(|reader| reader.detect(path))
+            .map(|reader| reader.as_ref())
+    }
+
+    pub fn read(&self, path: &Path) -> io::Result<Mesh> {
+        match self.detect(path) {
+            Some(reader) => reader.read(path),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{}: no reader recognizes this format", path.display()),
+            )),
+        }
+    }
+}
+
+pub fn load_mesh(folder: &str) -> io::Result<Mesh> {
+    let path = |name: &str| format!("{}/{}", folder, name);
+    let context = |name: &str| {
+        let path = path(name);
+        move |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path, e))
+    };
+
+    let points = read_mesh_from_file(&path("points")).map_err(context("points"))?;
+    let elements =
+        read_elements_from_file(&path("finite_elements")).map_err(context("finite_elements"))?;
+    let dirichlet = read_dirichlet_from_file(&path("dirichlet")).map_err(context("dirichlet"))?;
+    let neumann = read_neumann_from_file(&path("neumann")).map_err(context("neumann"))?;
+    let solution_path = path("solution");
+    let solution = if Path::new(&solution_path).exists() {
+        read_solution_from_file(&solution_path).map_err(context("solution"))?
+    } else {
+        vec![0.0; points.len()]
+    };
+    check_solution(&solution, points.len()).map_err(context("solution"))?;
+    let mut fields = vec![Field::new("solution", &solution_path, solution)];
+    let fields_path = path("fields");
+    if Path::new(&fields_path).is_dir() {
+        fields.extend(read_fields_from_folder(&fields_path).map_err(context("fields"))?);
+    } else if Path::new(&fields_path).exists() {
+        fields.extend(read_fields_from_file(&fields_path).map_err(context("fields"))?);
+    }
+
+    let robin_path = path("robin");
+    let robin = if Path::new(&robin_path).exists() {
+        Some(read_neumann_from_file(&robin_path).map_err(context("robin"))?)
+    } else {
+        None
+    };
+    check_nodes(
+        points.len(),
+        &elements,
+        &dirichlet,
+        &neumann,
+        robin.as_deref().unwrap_or_default(),
+    )
+    .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", folder, e)))?;
+
+    let mut mesh = Mesh::new(points, elements, dirichlet, neumann, fields);
+    if let Some(robin) = robin {
+        mesh.robin = robin;
+        mesh.update_uncovered_edges();
+    }
+
+    let optional_values = |name: &str, count: usize| -> io::Result<Vec<BoundaryValue>> {
+        let values_path = path(name);
+        if !Path::new(&values_path).exists() {
+            return Ok(Vec::new());
+        }
+        let values = read_boundary_values_from_file(&values_path).map_err(context(name))?;
+        if values.len() != count {
+            return Err(context(name)(invalid_data(format!(
+                "expected {} lines, found {}",
+                count,
+                values.len()
+            ))));
+        }
+        Ok(values)
+    };
+    let edge_values = |edges: &[Vec<usize>], values: Vec<BoundaryValue>| -> EdgeValues {
+        edges
+            .iter()
+            .zip(values)
+            .filter(|(edge, _)| edge.len() >= 2)
+            .map(|(edge, value)| (boundary::edge_key(edge), value))
+            .collect()
+    };
+    mesh.conditions = Conditions {
+        dirichlet: mesh
+            .dirichlet
+            .iter()
+            .copied()
+            .zip(optional_values("dirichlet_values", mesh.dirichlet.len())?)
+            .collect(),
+        neumann: edge_values(
+            &mesh.neumann,
+            optional_values("neumann_values", mesh.neumann.len())?,
+        ),
+        robin: edge_values(
+            &mesh.robin,
+            optional_values("robin_values", mesh.robin.len())?,
+        ),
+    };
+
+    Ok(mesh)
+}
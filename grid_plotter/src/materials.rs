@@ -0,0 +1,79 @@
+use eframe::egui::Color32;
+use serde::Deserialize;
+
+const PALETTE: [Color32; 6] = [
+    Color32::LIGHT_BLUE,
+    Color32::GREEN,
+    Color32::GRAY,
+    Color32::KHAKI,
+    Color32::DARK_RED,
+    Color32::YELLOW,
+];
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "PascalCase")]
+pub struct Material {
+    pub lambda: f64,
+    pub gamma: f64,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct MaterialProperties {
+    pub id: usize,
+    pub name: String,
+    pub color: [u8; 3],
+    #[serde(default)]
+    pub material: Option<Material>,
+}
+
+impl MaterialProperties {
+    pub fn fallback(id: usize) -> Self {
+        let color = PALETTE[id % PALETTE.len()];
+        Self {
+            id,
+            name: format!("Material {}", id),
+            color: [color.r(), color.g(), color.b()],
+            material: None,
+        }
+    }
+
+    pub fn color32(&self) -> Color32 {
+        Color32::from_rgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+pub fn material_entry(element: &[usize]) -> Option<usize> {
+    match element.len() {
+        9 | 10 => Some(8),
+        5 => Some(4),
+        _ => None,
+    }
+}
+
+pub fn element_material(element: &[usize]) -> Option<usize> {
+    material_entry(element).map(|entry| element[entry])
+}
+
+pub fn set_element_material(element: &mut [usize], id: usize) {
+    if let Some(entry) = material_entry(element) {
+        element[entry] = id;
+    }
+}
+
+pub fn complete_materials(
+    mut materials: Vec<MaterialProperties>,
+    elements: &[Vec<usize>],
+) -> Vec<MaterialProperties> {
+    for id in elements
+        .iter()
+        .filter_map(|element| element_material(element))
+    {
+        if !materials.iter().any(|material| material.id == id) {
+            materials.push(MaterialProperties::fallback(id));
+        }
+    }
+
+    materials.sort_by_key(|material| material.id);
+    materials
+}
@@ -0,0 +1,258 @@
+use std::borrow::Cow;
+
+use crate::boundary::{self, ConditionKind};
+use crate::field::{Field, FieldLocation};
+use crate::streamlines;
+
+pub fn element_nodes(element: &[usize]) -> Vec<usize> {
+    match element.len() {
+        10 => element[..8].iter().chain(&element[9..]).copied().collect(),
+        9 => element[..8].to_vec(),
+        5 => element[..4].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn element_outline(element: &[usize]) -> Vec<usize> {
+    match element.len() {
+        9 | 10 => element[..8].to_vec(),
+        5 => element[..4].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+pub struct Mesh {
+    pub points: Vec<(f64, f64)>,
+    pub elements: Vec<Vec<usize>>,
+    pub dirichlet: Vec<usize>,
+    pub neumann: Vec<Vec<usize>>,
+    pub robin: Vec<Vec<usize>>,
+    pub conditions: boundary::Conditions,
+    pub triangles: Vec<Vec<usize>>,
+    pub triangle_elements: Vec<usize>,
+    pub triangle_neighbors: Vec<[Option<usize>; 3]>,
+    pub boundary_edges: Vec<Vec<usize>>,
+    pub uncovered_edges: Vec<Vec<usize>>,
+    pub fields: Vec<Field>,
+    pub skipped_fields: Vec<String>,
+}
+
+impl Default for Mesh {
+    fn default() -> Self {
+        Mesh::new(Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new())
+    }
+}
+
+impl Mesh {
+    pub fn new(
+        points: Vec<(f64, f64)>,
+        elements: Vec<Vec<usize>>,
+        dirichlet: Vec<usize>,
+        neumann: Vec<Vec<usize>>,
+        fields: Vec<Field>,
+    ) -> Self {
+        let mut skipped_fields = Vec::new();
+        let mut skip = |field: &Field| {
+            skipped_fields.push(format!(
+                "{}: {} values for {} nodes and {} elements",
+                field.name,
+                field.values.len(),
+                points.len(),
+                elements.len()
+            ));
+        };
+        let mut fields = fields.into_iter();
+        let solution = match fields.next() {
+            Some(mut solution) if solution.values.len() == points.len() => {
+                solution.location = FieldLocation::Node;
+                solution
+            }
+            other => {
+                if let Some(solution) = &other {
+                    skip(solution);
+                }
+                Field::new("solution", "", vec![0.0; points.len()])
+            }
+        };
+        let fields = std::iter::once(solution)
+            .chain(fields.filter_map(|mut field| {
+                if field.values.len() == points.len() {
+                    field.location = FieldLocation::Node;
+                } else if field.values.len() == elements.len() {
+                    field.location = FieldLocation::Element;
+                } else {
+                    skip(&field);
+                    return None;
+                }
+                Some(field)
+            }))
+            .collect();
+
+        let (triangles, triangle_elements) = triangulate(&elements);
+        let triangle_neighbors = streamlines::triangle_neighbors(&triangles);
+        let boundary_edges = boundary::find_boundary_edges(&elements);
+        let uncovered_edges = boundary::find_uncovered_edges(&boundary_edges, &dirichlet, &neumann);
+
+        Self {
+            points,
+            elements,
+            dirichlet,
+            neumann,
+            robin: Vec::new(),
+            conditions: boundary::Conditions::default(),
+            triangles,
+            triangle_elements,
+            triangle_neighbors,
+            boundary_edges,
+            uncovered_edges,
+            fields,
+            skipped_fields,
+        }
+    }
+
+    pub fn update_uncovered_edges(&mut self) {
+        let covered: Vec<Vec<usize>> = self.neumann.iter().chain(&self.robin).cloned().collect();
+        self.uncovered_edges =
+            boundary::find_uncovered_edges(&self.boundary_edges, &self.dirichlet, &covered);
+    }
+
+    pub fn boundary_entries(&self) -> impl Iterator<Item = (ConditionKind, &[usize])> {
+        let dirichlet = self
+            .dirichlet
+            .iter()
+            .map(|node| (ConditionKind::Dirichlet, std::slice::from_ref(node)));
+        let neumann = self
+            .neumann
+            .iter()
+            .map(|edge| (ConditionKind::Neumann, edge.as_slice()));
+        let robin = self
+            .robin
+            .iter()
+            .map(|edge| (ConditionKind::Robin, edge.as_slice()));
+        dirichlet.chain(neumann.chain(robin).filter(|(_, edge)| edge.len() >= 2))
+    }
+
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+
+    pub fn set_field(&mut self, field: Field) -> usize {
+        match self.field_index(&field.name) {
+            Some(index) => {
+                self.fields[index] = field;
+                index
+            }
+            None => {
+                self.fields.push(field);
+                self.fields.len() - 1
+            }
+        }
+    }
+
+    pub fn nodal_values(&self, field: usize, average_cell_fields: bool) -> Option<Cow<'_, [f64]>> {
+        let field = &self.fields[field];
+        match field.location {
+            FieldLocation::Node => Some(Cow::Borrowed(&field.values)),
+            FieldLocation::Element if average_cell_fields => Some(Cow::Owned(
+                field.nodal_average(&self.elements, self.points.len()),
+            )),
+            FieldLocation::Element => None,
+        }
+    }
+
+    pub fn triangle_values(&self, field: usize, triangle: usize) -> [f64; 3] {
+        let field = &self.fields[field];
+        match field.location {
+            FieldLocation::Node => {
+                let nodes = &self.triangles[triangle];
+                [
+                    field.values[nodes[0]],
+                    field.values[nodes[1]],
+                    field.values[nodes[2]],
+                ]
+            }
+            FieldLocation::Element => [field.values[self.triangle_elements[triangle]]; 3],
+        }
+    }
+}
+
+pub fn triangulate(elements: &[Vec<usize>]) -> (Vec<Vec<usize>>, Vec<usize>) {
+    let mut triangles: Vec<Vec<usize>> = Vec::new();
+    let mut triangle_elements: Vec<usize> = Vec::new();
+
+    for (index, element) in elements.iter().enumerate() {
+        if element.len() == 10 {
+            triangles.push(vec![element[0], element[1], element[9]]);
+            triangles.push(vec![element[0], element[9], element[7]]);
+
+            triangles.push(vec![element[1], element[2], element[9]]);
+            triangles.push(vec![element[2], element[3], element[9]]);
+
+            triangles.push(vec![element[9], element[3], element[4]]);
+            triangles.push(vec![element[9], element[4], element[5]]);
+
+            triangles.push(vec![element[7], element[9], element[6]]);
+            triangles.push(vec![element[9], element[5], element[6]]);
+
+            triangle_elements.extend([index; 8]);
+        } else if element.len() == 9 {
+            triangles.push(vec![element[7], element[0], element[1]]);
+            triangles.push(vec![element[1], element[2], element[3]]);
+            triangles.push(vec![element[3], element[4], element[5]]);
+            triangles.push(vec![element[5], element[6], element[7]]);
+
+            triangles.push(vec![element[1], element[3], element[5]]);
+            triangles.push(vec![element[1], element[5], element[7]]);
+
+            triangle_elements.extend([index; 6]);
+        } else if element.len() == 5 {
+            triangles.push(vec![element[0], element[1], element[2]]);
+            triangles.push(vec![element[0], element[2], element[3]]);
+
+            triangle_elements.extend([index; 2]);
+        }
+    }
+
+    (triangles, triangle_elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interpolation, loaders};
+
+    fn outline_area(points: &[(f64, f64)], element: &[usize]) -> f64 {
+        let outline = element_outline(element);
+        let twice_area: f64 = outline
+            .iter()
+            .zip(outline.iter().cycle().skip(1))
+            .map(|(&a, &b)| points[a].0 * points[b].1 - points[b].0 * points[a].1)
+            .sum();
+        twice_area.abs() / 2.0
+    }
+
+    #[test]
+    fn triangles_cover_every_element_layout() {
+        for name in ["grid", "grid/hole", "grid/test_grid"] {
+            let mesh =
+                loaders::load_mesh(&format!("{}/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap();
+            for (index, element) in mesh.elements.iter().enumerate() {
+                let area: f64 = mesh
+                    .triangles
+                    .iter()
+                    .zip(&mesh.triangle_elements)
+                    .filter(|&(_, &parent)| parent == index)
+                    .map(|(triangle, _)| interpolation::triangle_area(&mesh.points, triangle))
+                    .sum();
+                let expected = outline_area(&mesh.points, element);
+                assert!(expected > 0.0);
+                assert!(
+                    (area - expected).abs() < 1e-9 * expected,
+                    "{} element {}",
+                    name,
+                    index
+                );
+            }
+        }
+    }
+}
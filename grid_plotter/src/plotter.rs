@@ -0,0 +1,2469 @@
+use eframe::egui::{self, Color32, DragValue, Event, RichText, Vec2};
+use egui_plot::{Arrows, Legend, Line, PlotPoints, Polygon};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::boundary::ConditionKind;
+use crate::commands::{self, Command};
+use crate::editing::{self, Edit, EditTool};
+use crate::field::{Field, FieldLocation, SOLUTION_FIELD};
+use crate::layers::{self, LayerKind};
+use crate::settings::{self, SeedMode, VectorKind};
+use crate::{
+    color, contour, convergence, expression, interpolation, loaders, materials, mesh, refinement,
+    sparsity, statistics, streamlines, symmetry, timeline, transfer,
+};
+
+const PICK_RADIUS: f64 = 8.0;
+const LABEL_INSET: f32 = 14.0;
+const SETTINGS_SAVE_DELAY: f64 = 1.0;
+
+#[derive(PartialEq, Clone, Copy)]
+enum CompareMode {
+    Off,
+    SideBySide,
+    Split,
+}
+
+struct SymmetryCache {
+    symmetry: symmetry::Symmetry,
+    mesh: mesh::Mesh,
+    comparison: Option<mesh::Mesh>,
+}
+
+struct VectorArrows {
+    origins: Vec<[f64; 2]>,
+    tips: Vec<[f64; 2]>,
+}
+
+struct VectorCache {
+    settings: (VectorKind, bool, u16, f32),
+    primary: VectorArrows,
+    comparison: Option<VectorArrows>,
+}
+
+struct PlotOutput {
+    rect: egui::Rect,
+    bounds: egui_plot::PlotBounds,
+    hovered: bool,
+    clicked: Option<[f64; 2]>,
+    pointer: Option<[f64; 2]>,
+    pick_radius: f64,
+}
+
+pub struct GridPlotter {
+    readers: loaders::ReaderRegistry,
+    settings: settings::Settings,
+    settings_file: settings::SettingsFile,
+    settings_path: Option<PathBuf>,
+    unsaved_settings: Option<(settings::Settings, f64)>,
+    preset_name: String,
+    settings_status: String,
+    mesh: mesh::Mesh,
+    comparison: Option<mesh::Mesh>,
+    compare_mode: CompareMode,
+    comparison_folder: String,
+    comparison_status: String,
+    edit_mode: bool,
+    edit_tool: EditTool,
+    edit_material: usize,
+    dragged_node: Option<(usize, (f64, f64))>,
+    history: editing::EditHistory,
+    sparsity: Option<sparsity::Sparsity>,
+    symmetry_cache: Option<SymmetryCache>,
+    vector_cache: Option<VectorCache>,
+    save_folder: String,
+    save_status: String,
+    linked_bounds: Option<egui_plot::PlotBounds>,
+    current_bounds: Option<egui_plot::PlotBounds>,
+    requested_bounds: Option<egui_plot::PlotBounds>,
+    view_name: String,
+    initial_view: Option<settings::View>,
+    reset_view_requested: bool,
+    show_palette: bool,
+    palette_query: String,
+    difference_norms: Option<transfer::DifferenceNorms>,
+    convergence: convergence::ConvergenceStudy,
+    reference_text: String,
+    reference: Option<expression::Expression>,
+    reference_status: String,
+    reference_norms: Option<transfer::DifferenceNorms>,
+    heatmap_field: usize,
+    contour_field: usize,
+    materials: Vec<materials::MaterialProperties>,
+    hidden_materials: HashSet<usize>,
+    timeline: Option<timeline::Timeline>,
+    export_frame: Option<usize>,
+    export_requested: bool,
+    export_status: String,
+    plot_rect: Option<egui::Rect>,
+    streamline_seeds: Vec<[f64; 2]>,
+    seed_line_start: Option<[f64; 2]>,
+    streamlines: Vec<Vec<[f64; 2]>>,
+}
+
+impl Default for GridPlotter {
+    fn default() -> Self {
+        Self {
+            readers: loaders::ReaderRegistry::default(),
+            settings: settings::Settings::default(),
+            settings_file: settings::SettingsFile::default(),
+            settings_path: None,
+            unsaved_settings: None,
+            preset_name: String::new(),
+            settings_status: String::new(),
+            mesh: mesh::Mesh::default(),
+            comparison: None,
+            compare_mode: CompareMode::Off,
+            comparison_folder: String::new(),
+            comparison_status: String::new(),
+            edit_mode: false,
+            edit_tool: EditTool::MoveNode,
+            edit_material: 1,
+            dragged_node: None,
+            history: editing::EditHistory::default(),
+            sparsity: None,
+            symmetry_cache: None,
+            vector_cache: None,
+            save_folder: String::new(),
+            save_status: String::new(),
+            linked_bounds: None,
+            current_bounds: None,
+            requested_bounds: None,
+            view_name: String::new(),
+            initial_view: None,
+            reset_view_requested: false,
+            show_palette: false,
+            palette_query: String::new(),
+            difference_norms: None,
+            convergence: convergence::ConvergenceStudy::default(),
+            reference_text: String::new(),
+            reference: None,
+            reference_status: String::new(),
+            reference_norms: None,
+            heatmap_field: SOLUTION_FIELD,
+            contour_field: SOLUTION_FIELD,
+            materials: Vec::new(),
+            hidden_materials: HashSet::new(),
+            timeline: None,
+            export_frame: None,
+            export_requested: false,
+            export_status: String::new(),
+            plot_rect: None,
+            streamline_seeds: Vec::new(),
+            seed_line_start: None,
+            streamlines: Vec::new(),
+        }
+    }
+}
+
+impl GridPlotter {
+    pub fn new(mesh: mesh::Mesh, materials: Vec<materials::MaterialProperties>) -> Self {
+        let materials = materials::complete_materials(materials, &mesh.elements);
+
+        Self {
+            edit_material: materials.first().map_or(1, |material| material.id),
+            mesh,
+            materials,
+            ..Self::default()
+        }
+    }
+
+    pub fn load_settings(&mut self, path: &Path) {
+        self.settings_path = Some(path.to_path_buf());
+        if !path.exists() {
+            return;
+        }
+        match settings::SettingsFile::load(&path.to_string_lossy()) {
+            Ok(file) => {
+                self.settings = file.current.clone();
+                self.settings_file = file;
+            }
+            Err(e) => self.settings_status = format!("Failed to read settings: {}", e),
+        }
+    }
+
+    fn save_settings(&mut self) {
+        self.unsaved_settings = None;
+        self.settings_file.current = self.settings.clone();
+        let Some(path) = &self.settings_path else {
+            return;
+        };
+        if let Err(e) = self.settings_file.save(&path.to_string_lossy()) {
+            self.settings_status = format!("Failed to save settings: {}", e);
+        }
+    }
+
+    fn save_settings_when_idle(&mut self, ctx: &egui::Context) {
+        if self.settings == self.settings_file.current {
+            self.unsaved_settings = None;
+            return;
+        }
+        let (now, dragging) = ctx.input(|i| (i.time, i.pointer.any_down()));
+        match &self.unsaved_settings {
+            Some((settings, changed)) if *settings == self.settings => {
+                if !dragging && now - changed >= SETTINGS_SAVE_DELAY {
+                    self.save_settings();
+                    return;
+                }
+            }
+            _ => self.unsaved_settings = Some((self.settings.clone(), now)),
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(SETTINGS_SAVE_DELAY));
+    }
+
+    pub fn register_reader(&mut self, reader: Box<dyn loaders::MeshReader>) {
+        self.readers.register(reader);
+    }
+
+    pub fn set_grid_folder(&mut self, folder: &str) {
+        self.save_folder = folder.to_string();
+    }
+
+    pub fn set_initial_view(&mut self, view: settings::View) {
+        self.initial_view = Some(view);
+        self.set_view(view);
+    }
+
+    pub fn set_view(&mut self, view: settings::View) {
+        self.requested_bounds = Some(egui_plot::PlotBounds::from_min_max(view.min, view.max));
+    }
+
+    fn reset_view(&mut self) {
+        match self.initial_view {
+            Some(view) => self.set_view(view),
+            None => self.reset_view_requested = true,
+        }
+    }
+
+    fn fit_to_mesh(&mut self) {
+        if self.mesh.points.is_empty() {
+            return;
+        }
+        self.update_symmetry_cache();
+        let points = self
+            .symmetry_cache
+            .as_ref()
+            .map_or(&self.mesh.points, |cache| &cache.mesh.points);
+        let (min, max) = interpolation::bounding_box(points);
+        let margin = 0.05 * (max[0] - min[0]).max(max[1] - min[1]);
+        self.set_view(settings::View {
+            min: [min[0] - margin, min[1] - margin],
+            max: [max[0] + margin, max[1] + margin],
+        });
+    }
+
+    fn run_command(&mut self, command: Command) {
+        let settings = &mut self.settings;
+        match command {
+            Command::ToggleHeatmap => settings.toggle_layer(LayerKind::Heatmap),
+            Command::ToggleContours => settings.toggle_layer(LayerKind::Contours),
+            Command::TogglePoints => settings.toggle_layer(LayerKind::Nodes),
+            Command::ToggleNumbers => settings.toggle_layer(LayerKind::Labels),
+            Command::ToggleTriangles => settings.show_triangles ^= true,
+            Command::ToggleGrid => settings.show_grid ^= true,
+            Command::ToggleMaterials => settings.toggle_layer(LayerKind::Materials),
+            Command::ToggleBoundary => settings.toggle_layer(LayerKind::Boundary),
+            Command::ToggleVectors => settings.toggle_layer(LayerKind::Vectors),
+            Command::ToggleStreamlines => settings.toggle_layer(LayerKind::Streamlines),
+            Command::ToggleStatistics => settings.show_statistics ^= true,
+            Command::ResetView => self.reset_view(),
+            Command::FitToMesh => self.fit_to_mesh(),
+            Command::NextTimeStep | Command::PreviousTimeStep => {
+                if let Some(timeline) = &self.timeline {
+                    let layer = if command == Command::NextTimeStep {
+                        (timeline.current + 1).min(timeline.frames_count().saturating_sub(1))
+                    } else {
+                        timeline.current.saturating_sub(1)
+                    };
+                    self.set_time_layer(layer);
+                }
+            }
+            Command::PlayPause => {
+                if let Some(timeline) = &mut self.timeline {
+                    timeline.playing = !timeline.playing;
+                }
+            }
+            Command::ToggleEditMode => {
+                self.edit_mode = !self.edit_mode;
+                self.dragged_node = None;
+            }
+            Command::Undo => {
+                if self.history.undo(&mut self.mesh) {
+                    self.mesh_edited();
+                }
+            }
+            Command::Redo => {
+                if self.history.redo(&mut self.mesh) {
+                    self.mesh_edited();
+                }
+            }
+            Command::OpenPalette => {
+                self.show_palette = !self.show_palette;
+                self.palette_query.clear();
+            }
+        }
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() && !self.show_palette {
+            return;
+        }
+
+        let mut shortcuts: Vec<(Command, egui::KeyboardShortcut)> = self
+            .settings_file
+            .bindings
+            .iter()
+            .filter_map(|(&command, text)| Some((command, commands::parse_shortcut(text)?)))
+            .filter(|&(command, _)| !self.show_palette || command == Command::OpenPalette)
+            .collect();
+        shortcuts.sort_by_key(|(_, shortcut)| {
+            let modifiers = shortcut.modifiers;
+            std::cmp::Reverse(modifiers.ctrl as u8 + modifiers.shift as u8 + modifiers.alt as u8)
+        });
+
+        let triggered: Vec<Command> = ctx.input_mut(|i| {
+            shortcuts
+                .iter()
+                .filter(|(_, shortcut)| i.consume_shortcut(shortcut))
+                .map(|&(command, _)| command)
+                .collect()
+        });
+        for command in triggered {
+            self.run_command(command);
+        }
+    }
+
+    fn show_command_palette(&mut self, ctx: &egui::Context) {
+        let mut run = None;
+        let mut open = self.show_palette;
+
+        egui::Window::new("Command palette")
+            .open(&mut open)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_TOP, Vec2::new(0.0, 40.0))
+            .show(ctx, |ui| {
+                let response = ui.text_edit_singleline(&mut self.palette_query);
+                response.request_focus();
+
+                let found: Vec<Command> = Command::ALL
+                    .into_iter()
+                    .filter(|&command| {
+                        command != Command::OpenPalette
+                            && commands::matches(command, &self.palette_query)
+                    })
+                    .collect();
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    run = found.first().copied();
+                }
+
+                for command in found {
+                    let shortcut = self
+                        .settings_file
+                        .bindings
+                        .get(&command)
+                        .and_then(|text| commands::parse_shortcut(text))
+                        .map(|shortcut| ctx.format_shortcut(&shortcut))
+                        .unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        if ui.button(command.name()).clicked() {
+                            run = Some(command);
+                        }
+                        ui.weak(shortcut);
+                    });
+                }
+            });
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            open = false;
+        }
+        self.show_palette = open && run.is_none();
+        if let Some(command) = run {
+            self.run_command(command);
+        }
+    }
+
+    fn layers_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Layers", |ui| {
+            ui.label("Drawn from top to bottom of the list");
+            let count = self.settings.layers.len();
+            let mut swap = None;
+            for (index, layer) in self.settings.layers.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut layer.visible, layer.kind.name());
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("⏶"))
+                        .on_hover_text("Draw this layer earlier")
+                        .clicked()
+                    {
+                        swap = Some(index - 1);
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("⏷"))
+                        .on_hover_text("Draw this layer later")
+                        .clicked()
+                    {
+                        swap = Some(index);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.color_edit_button_srgb(&mut layer.color);
+                    ui.add(egui::Slider::new(&mut layer.opacity, 0.0..=1.0).text("Opacity"));
+                });
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut layer.size).range(0.5..=50.0).speed(0.1));
+                    ui.label(layer.kind.size_label());
+                });
+                ui.separator();
+            }
+            if let Some(index) = swap {
+                self.settings.layers.swap(index, index + 1);
+            }
+            if ui.button("Reset layers").clicked() {
+                self.settings.layers = layers::default_layers();
+            }
+        });
+    }
+
+    fn shortcuts_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Shortcuts", |ui| {
+            let mut changed = false;
+            egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+                for (command, text) in &mut self.settings_file.bindings {
+                    ui.label(command.name());
+                    let valid = text.is_empty() || commands::parse_shortcut(text).is_some();
+                    let edit = egui::TextEdit::singleline(text)
+                        .desired_width(100.0)
+                        .text_color_opt((!valid).then_some(Color32::RED));
+                    changed |= ui
+                        .add(edit)
+                        .on_hover_text("Modifiers and a key joined with +, e.g. Ctrl+Shift+H")
+                        .lost_focus();
+                    ui.end_row();
+                }
+            });
+            if ui.button("Default shortcuts").clicked() {
+                self.settings_file.bindings = commands::default_bindings();
+                changed = true;
+            }
+            if changed {
+                self.save_settings();
+            }
+        });
+    }
+
+    fn views_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Views", |ui| {
+            if let Some(bounds) = self.current_bounds {
+                let (min, max) = (bounds.min(), bounds.max());
+                ui.label(format!(
+                    "x: {:.4} .. {:.4}, y: {:.4} .. {:.4}",
+                    min[0], max[0], min[1], max[1]
+                ))
+                .on_hover_text(format!(
+                    "Reproduce with --bounds {},{},{},{}",
+                    min[0], min[1], max[0], max[1]
+                ));
+            }
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.view_name)
+                    .on_hover_text("View name");
+                let enabled = !self.view_name.is_empty() && self.current_bounds.is_some();
+                if ui
+                    .add_enabled(enabled, egui::Button::new("Save view"))
+                    .clicked()
+                {
+                    let bounds = self.current_bounds.unwrap();
+                    let view = settings::View {
+                        min: bounds.min(),
+                        max: bounds.max(),
+                    };
+                    self.settings_file
+                        .views
+                        .insert(self.view_name.clone(), view);
+                    self.save_settings();
+                }
+            });
+
+            let mut show = None;
+            let mut remove = None;
+            for (name, view) in &self.settings_file.views {
+                ui.horizontal(|ui| {
+                    ui.label(name);
+                    if ui.button("Go").clicked() {
+                        show = Some(*view);
+                    }
+                    if ui.button("Delete").clicked() {
+                        remove = Some(name.clone());
+                    }
+                });
+            }
+            if let Some(view) = show {
+                self.set_view(view);
+            }
+            if let Some(name) = remove {
+                self.settings_file.views.remove(&name);
+                self.save_settings();
+            }
+        });
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Settings", |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.preset_name)
+                    .on_hover_text("Preset name");
+                if ui
+                    .add_enabled(
+                        !self.preset_name.is_empty(),
+                        egui::Button::new("Save preset"),
+                    )
+                    .clicked()
+                {
+                    self.settings_file
+                        .presets
+                        .insert(self.preset_name.clone(), self.settings.clone());
+                    self.save_settings();
+                }
+            });
+
+            let mut load = None;
+            let mut remove = None;
+            for name in self.settings_file.presets.keys() {
+                ui.horizontal(|ui| {
+                    ui.label(name);
+                    if ui.button("Load").clicked() {
+                        load = Some(name.clone());
+                    }
+                    if ui.button("Delete").clicked() {
+                        remove = Some(name.clone());
+                    }
+                });
+            }
+            if let Some(name) = load {
+                self.settings = self.settings_file.presets[&name].clone();
+                self.preset_name = name;
+                self.rebuild_streamlines();
+            }
+            if let Some(name) = remove {
+                self.settings_file.presets.remove(&name);
+                self.save_settings();
+            }
+
+            if ui
+                .button("Reset to defaults")
+                .on_hover_text("Restore every viewer setting to its default value")
+                .clicked()
+            {
+                self.settings = settings::Settings::default();
+                self.rebuild_streamlines();
+            }
+            if !self.settings_status.is_empty() {
+                ui.label(&self.settings_status);
+            }
+        });
+    }
+
+    pub fn set_comparison(&mut self, comparison: mesh::Mesh, folder: &str) {
+        let materials = std::mem::take(&mut self.materials);
+        self.materials = materials::complete_materials(materials, &comparison.elements);
+        self.comparison = Some(comparison);
+        self.invalidate_display_caches();
+        self.comparison_folder = folder.to_string();
+        self.comparison_status = format!("Loaded {}", folder);
+        if self.compare_mode == CompareMode::Off {
+            self.compare_mode = CompareMode::SideBySide;
+        }
+    }
+
+    fn transfer_comparison(&mut self) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+
+        let source = comparison.fields[SOLUTION_FIELD].source.clone();
+        let transferred = transfer::transfer(comparison, SOLUTION_FIELD, &self.mesh);
+        let (difference, norms) =
+            transfer::difference_norms(&self.mesh, self.solution(), &transferred);
+        let transferred = transferred
+            .iter()
+            .map(|value| value.unwrap_or(0.0))
+            .collect();
+
+        self.mesh
+            .set_field(Field::new("transferred solution", &source, transferred));
+        self.heatmap_field =
+            self.mesh
+                .set_field(Field::new("solution difference", &source, difference));
+        self.difference_norms = Some(norms);
+        self.invalidate_display_caches();
+    }
+
+    fn apply_reference(&mut self) {
+        match expression::Expression::parse(&self.reference_text) {
+            Ok(reference) => {
+                self.reference = Some(reference);
+                self.reference_status.clear();
+                self.update_reference_fields();
+                if let Some(index) = self.mesh.field_index("reference error") {
+                    self.heatmap_field = index;
+                }
+            }
+            Err(e) => self.reference_status = e,
+        }
+    }
+
+    fn update_reference_fields(&mut self) {
+        let Some(reference) = &self.reference else {
+            return;
+        };
+
+        let t = self
+            .timeline
+            .as_ref()
+            .map_or(0.0, |timeline| timeline.times[timeline.current]);
+        let values = reference.evaluate_at_nodes(&self.mesh.points, t);
+        let exact: Vec<Option<f64>> = values.iter().copied().map(Some).collect();
+        let (error, norms) = transfer::difference_norms(&self.mesh, self.solution(), &exact);
+
+        self.mesh
+            .set_field(Field::new("reference", &self.reference_text, values));
+        self.mesh
+            .set_field(Field::new("reference error", &self.reference_text, error));
+        self.reference_norms = Some(norms);
+        self.invalidate_display_caches();
+    }
+
+    fn reference_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Reference solution", |ui| {
+            ui.horizontal(|ui| {
+                let response = ui
+                    .text_edit_singleline(&mut self.reference_text)
+                    .on_hover_text("Expression in x, y and t, e.g. sin(x) + y^2");
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Apply").clicked() || submitted {
+                    self.apply_reference();
+                }
+            });
+            if !self.reference_status.is_empty() {
+                ui.colored_label(Color32::RED, &self.reference_status);
+            }
+            if let Some(norms) = &self.reference_norms {
+                norms_grid(ui, "reference norms", norms, "Nodes");
+            }
+        });
+    }
+
+    fn comparing(&self) -> Option<&mesh::Mesh> {
+        match self.compare_mode {
+            CompareMode::Off => None,
+            _ => self.comparison.as_ref(),
+        }
+    }
+
+    fn comparison_field(&self, comparison: &mesh::Mesh, field: usize) -> usize {
+        comparison
+            .field_index(&self.mesh.fields[field].name)
+            .unwrap_or(SOLUTION_FIELD)
+    }
+
+    fn build_materials(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi,
+        mesh: &mesh::Mesh,
+        layer: &layers::Layer,
+    ) {
+        for element in &mesh.elements {
+            let Some(material) = self
+                .material(element)
+                .filter(|material| !self.hidden_materials.contains(&material.id))
+            else {
+                continue;
+            };
+
+            let vertices: Vec<[f64; 2]> = mesh::element_outline(element)
+                .iter()
+                .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                .collect();
+            plot_ui.polygon(
+                Polygon::new(vertices)
+                    .fill_color(layer.tint(material.color32()))
+                    .stroke(egui::Stroke::new(layer.size, layer.color32()))
+                    .name(&material.name),
+            );
+        }
+    }
+
+    fn build_heatmap(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi,
+        mesh: &mesh::Mesh,
+        field: usize,
+        (minimum, maximum): (f64, f64),
+        layer: &layers::Layer,
+        bands: Option<u16>,
+    ) {
+        let heatmap_field = &mesh.fields[field];
+        let heat_color = |value: f64| {
+            let value = match bands {
+                Some(count) => color::band_value(value, maximum, minimum, count),
+                None => value,
+            };
+            layer.tint(color::interpolate_heat_color(value, maximum, minimum))
+        };
+
+        if heatmap_field.location == FieldLocation::Element {
+            for (index, element) in mesh.elements.iter().enumerate() {
+                if !self.element_visible(element) {
+                    continue;
+                }
+
+                let vertices: Vec<[f64; 2]> = mesh::element_outline(element)
+                    .iter()
+                    .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                    .collect();
+
+                plot_ui.polygon(
+                    Polygon::new(vertices)
+                        .fill_color(heat_color(heatmap_field.values[index]))
+                        .stroke(egui::Stroke::new(layer.size, layer.color32())),
+                );
+            }
+            return;
+        }
+
+        for (index, element) in mesh.triangles.iter().enumerate() {
+            if !self.triangle_visible(mesh, index) {
+                continue;
+            }
+
+            let vertices: Vec<[f64; 2]> = element
+                .iter()
+                .take(3)
+                .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                .collect();
+
+            let avg_value = element
+                .iter()
+                .take(3)
+                .map(|&i| heatmap_field.values[i])
+                .sum::<f64>()
+                / 3.0;
+            let color = heat_color(avg_value);
+
+            let stroke = if self.settings.show_triangles {
+                egui::Stroke::new(layer.size, layer.color32())
+            } else {
+                egui::Stroke::new(1.0, color)
+            };
+            plot_ui.polygon(Polygon::new(vertices).fill_color(color).stroke(stroke));
+        }
+    }
+
+    fn build_local_numbers(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi,
+        mesh: &mesh::Mesh,
+        layer: &layers::Layer,
+    ) {
+        for element in &mesh.elements {
+            let Some(centroid) = element_centroid(mesh, element) else {
+                continue;
+            };
+            let centroid = plot_ui.screen_from_plot(centroid.into());
+            let material = materials::material_entry(element);
+
+            for (entry, &node) in element.iter().enumerate() {
+                if Some(entry) == material {
+                    continue;
+                }
+                let (x, y) = mesh.points[node];
+                let position = plot_ui.screen_from_plot([x, y].into());
+                let inset = (centroid - position).normalized() * LABEL_INSET;
+                plot_ui.text(
+                    egui_plot::Text::new(
+                        plot_ui.plot_from_screen(position + inset),
+                        RichText::new(format!("{}", entry)).size(layer.size),
+                    )
+                    .color(layer.color32()),
+                );
+            }
+        }
+    }
+
+    fn build_orientation(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi,
+        mesh: &mesh::Mesh,
+        layer: &layers::Layer,
+    ) {
+        let mut origins = Vec::new();
+        let mut tips = Vec::new();
+        let mut reversed_origins = Vec::new();
+        let mut reversed_tips = Vec::new();
+
+        for element in &mesh.elements {
+            let outline = mesh::element_outline(element);
+            let Some(centroid) = element_centroid(mesh, element) else {
+                continue;
+            };
+            let centroid = plot_ui.screen_from_plot(centroid.into());
+            let corner = |entry: usize| {
+                let (x, y) = mesh.points[outline[entry]];
+                let position = plot_ui.screen_from_plot([x, y].into());
+                let inset = (centroid - position).normalized() * LABEL_INSET;
+                let point = plot_ui.plot_from_screen(position + inset);
+                [point.x, point.y]
+            };
+            let second = outline.len() / 4;
+
+            let area: f64 = (0..outline.len())
+                .map(|k| {
+                    let (ax, ay) = mesh.points[outline[k]];
+                    let (bx, by) = mesh.points[outline[(k + 1) % outline.len()]];
+                    ax * by - bx * ay
+                })
+                .sum();
+            if area < 0.0 {
+                reversed_origins.push(corner(0));
+                reversed_tips.push(corner(second));
+            } else {
+                origins.push(corner(0));
+                tips.push(corner(second));
+            }
+        }
+
+        plot_ui.arrows(
+            Arrows::new(origins, tips)
+                .color(layer.color32())
+                .name("Element orientation"),
+        );
+        plot_ui.arrows(
+            Arrows::new(reversed_origins, reversed_tips)
+                .color(layer.tint(Color32::RED))
+                .name("Clockwise elements"),
+        );
+    }
+
+    fn build_boundary_types(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi,
+        mesh: &mesh::Mesh,
+        layer: &layers::Layer,
+    ) {
+        let mut dirichlet: BTreeMap<usize, Vec<[f64; 2]>> = BTreeMap::new();
+        for (kind, nodes) in mesh.boundary_entries() {
+            let type_id = mesh
+                .conditions
+                .get(kind, nodes)
+                .map_or(0, |value| value.type_id);
+            let points: Vec<[f64; 2]> = nodes
+                .iter()
+                .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                .collect();
+            if kind == ConditionKind::Dirichlet {
+                dirichlet.entry(type_id).or_default().extend(points);
+                continue;
+            }
+            plot_ui.line(
+                Line::new(points)
+                    .name(format!("{} type {}", kind.name(), type_id))
+                    .color(layer.tint(color::category_color(type_id)))
+                    .width(layer.size),
+            );
+        }
+
+        for (type_id, points) in dirichlet {
+            plot_ui.points(
+                egui_plot::Points::new(points)
+                    .name(format!("Dirichlet type {}", type_id))
+                    .shape(egui_plot::MarkerShape::Square)
+                    .radius(1.5 * layer.size)
+                    .color(layer.tint(color::category_color(type_id))),
+            );
+        }
+    }
+
+    fn boundary_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Boundary conditions", |ui| {
+            ui.label(format!(
+                "Uncovered boundary edges: {}",
+                self.mesh.uncovered_edges.len()
+            ));
+            let summary = self.mesh.conditions.summary(self.mesh.boundary_entries());
+            egui::Grid::new("boundary types")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label("Condition");
+                    ui.label("Type");
+                    ui.label("Count");
+                    ui.label("Min value");
+                    ui.label("Max value");
+                    ui.end_row();
+
+                    for ((kind, type_id), entry) in &summary {
+                        ui.colored_label(color::category_color(*type_id), "⬛");
+                        ui.label(kind.name());
+                        ui.label(type_id.to_string());
+                        ui.label(entry.count.to_string());
+                        if entry.minimum <= entry.maximum {
+                            ui.label(format!("{:.6}", entry.minimum));
+                            ui.label(format!("{:.6}", entry.maximum));
+                        } else {
+                            ui.label("-");
+                            ui.label("-");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn build_isolines(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi,
+        mesh: &mesh::Mesh,
+        field: usize,
+        (minimum, maximum): (f64, f64),
+        layer: &layers::Layer,
+    ) {
+        let Some(values) = mesh.nodal_values(field, self.settings.average_cell_fields) else {
+            return;
+        };
+        for level in contour::isoline_levels(minimum, maximum, self.settings.isolines_count) {
+            let isoline_segments =
+                contour::isoline_segments(&mesh.points, &mesh.triangles, &values, level, |index| {
+                    self.triangle_visible(mesh, index)
+                });
+
+            for segment in isoline_segments {
+                let points = vec![segment.0, segment.1];
+                let plot_points: PlotPoints = points.into();
+                plot_ui.line(
+                    Line::new(plot_points)
+                        .color(layer.color32())
+                        .width(layer.size),
+                );
+            }
+        }
+    }
+
+    fn material(&self, element: &[usize]) -> Option<&materials::MaterialProperties> {
+        let id = materials::element_material(element)?;
+        self.materials.iter().find(|material| material.id == id)
+    }
+
+    fn element_lambda(&self, element: &[usize]) -> f64 {
+        match self.material(element) {
+            Some(materials::MaterialProperties {
+                material: Some(material),
+                ..
+            }) => material.lambda,
+            _ => 1.0,
+        }
+    }
+
+    pub fn set_timeline(&mut self, timeline: timeline::Timeline) -> Result<(), String> {
+        timeline.validate(self.mesh.points.len())?;
+        self.timeline = Some(timeline);
+        self.set_time_layer(0);
+        Ok(())
+    }
+
+    fn set_time_layer(&mut self, layer: usize) {
+        if let Some(timeline) = &mut self.timeline {
+            timeline.current = layer;
+            self.mesh.fields[SOLUTION_FIELD].values = timeline.layers[layer].clone();
+            self.invalidate_display_caches();
+            self.update_reference_fields();
+            self.rebuild_streamlines();
+        }
+    }
+
+    fn value_range(&self, field: usize) -> (f64, f64) {
+        let (minimum, maximum) = match &self.timeline {
+            Some(timeline) if field == SOLUTION_FIELD && self.settings.fixed_color_range => {
+                timeline.range()
+            }
+            _ => self.mesh.fields[field].range(),
+        };
+        let (minimum, maximum) = if field == SOLUTION_FIELD && self.display_mesh_active() {
+            self.settings.symmetry.value_range((minimum, maximum))
+        } else {
+            (minimum, maximum)
+        };
+
+        match self.comparing() {
+            Some(comparison) => {
+                let (other_minimum, other_maximum) =
+                    comparison.fields[self.comparison_field(comparison, field)].range();
+                (minimum.min(other_minimum), maximum.max(other_maximum))
+            }
+            None => (minimum, maximum),
+        }
+    }
+
+    fn display_mesh_active(&self) -> bool {
+        !self.edit_mode && !self.settings.symmetry.is_identity()
+    }
+
+    fn invalidate_display_caches(&mut self) {
+        self.symmetry_cache = None;
+        self.vector_cache = None;
+    }
+
+    fn displayed_meshes(&self) -> (&mesh::Mesh, Option<&mesh::Mesh>) {
+        match &self.symmetry_cache {
+            Some(cache) => (&cache.mesh, cache.comparison.as_ref()),
+            None => (&self.mesh, self.comparison.as_ref()),
+        }
+    }
+
+    fn update_symmetry_cache(&mut self) {
+        if !self.display_mesh_active() {
+            if self.symmetry_cache.take().is_some() {
+                self.vector_cache = None;
+            }
+            return;
+        }
+        let symmetry = self.settings.symmetry;
+        if self
+            .symmetry_cache
+            .as_ref()
+            .is_some_and(|cache| cache.symmetry == symmetry)
+        {
+            return;
+        }
+
+        self.vector_cache = None;
+        self.symmetry_cache = Some(SymmetryCache {
+            symmetry,
+            mesh: symmetry::replicate(&self.mesh, &symmetry),
+            comparison: self
+                .comparison
+                .as_ref()
+                .map(|comparison| symmetry::replicate(comparison, &symmetry)),
+        });
+    }
+
+    fn symmetry_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Symmetry", |ui| {
+            let symmetry = &mut self.settings.symmetry;
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut symmetry.mirror_x, "Mirror about x axis").on_hover_text("Check to show the domain reflected about the horizontal line through the center");
+                ui.checkbox(&mut symmetry.negate_x, "Flip sign").on_hover_text("Check to negate the solution in the copy reflected about the x axis");
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut symmetry.mirror_y, "Mirror about y axis").on_hover_text("Check to show the domain reflected about the vertical line through the center");
+                ui.checkbox(&mut symmetry.negate_y, "Flip sign").on_hover_text("Check to negate the solution in the copy reflected about the y axis");
+            });
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut symmetry.copies).range(1..=64));
+                ui.label("Rotational copies").on_hover_text("Number of copies of the domain rotated around the center");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Center");
+                ui.add(DragValue::new(&mut symmetry.center[0]).speed(0.01).prefix("x: "));
+                ui.add(DragValue::new(&mut symmetry.center[1]).speed(0.01).prefix("y: "));
+            });
+            if ui.button("Reset symmetry").clicked() {
+                *symmetry = symmetry::Symmetry::default();
+            }
+            if self.edit_mode && !self.settings.symmetry.is_identity() {
+                ui.label("Symmetric copies are hidden in edit mode");
+            }
+        });
+    }
+
+    fn solution(&self) -> &[f64] {
+        &self.mesh.fields[SOLUTION_FIELD].values
+    }
+
+    fn fields_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            field_selector(
+                ui,
+                "Heatmap field",
+                &self.mesh.fields,
+                &mut self.heatmap_field,
+            );
+            ui.label("Heatmap field");
+        });
+        ui.horizontal(|ui| {
+            field_selector(
+                ui,
+                "Contour field",
+                &self.mesh.fields,
+                &mut self.contour_field,
+            );
+            ui.label("Contour field");
+        });
+        ui.checkbox(
+            &mut self.settings.average_cell_fields,
+            "Average element fields to nodes",
+        )
+        .on_hover_text("Check to build contours of element fields from nodal averages");
+        ui.collapsing("Fields", |ui| {
+            egui::Grid::new("fields metadata")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Name");
+                    ui.label("Values");
+                    ui.label("Min");
+                    ui.label("Max");
+                    ui.label("Source");
+                    ui.end_row();
+
+                    for field in &self.mesh.fields {
+                        let (minimum, maximum) = field.range();
+                        let location = match field.location {
+                            FieldLocation::Node => "node",
+                            FieldLocation::Element => "element",
+                        };
+                        ui.label(format!("{} ({})", field.name, location));
+                        ui.label(field.values.len().to_string());
+                        ui.label(format!("{:.6}", minimum));
+                        ui.label(format!("{:.6}", maximum));
+                        ui.label(&field.source);
+                        ui.end_row();
+                    }
+                });
+            if !self.mesh.skipped_fields.is_empty() {
+                ui.label("Skipped fields whose size matches neither the nodes nor the elements:");
+                for skipped in &self.mesh.skipped_fields {
+                    ui.label(skipped);
+                }
+            }
+        });
+    }
+
+    fn timeline_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(timeline) = &mut self.timeline else {
+            return;
+        };
+
+        let mut layer = timeline.current;
+        let last = timeline.frames_count().saturating_sub(1);
+        ui.add(egui::Slider::new(&mut layer, 0..=last).text("Time layer"));
+        ui.label(format!("t = {}", timeline.times[layer]));
+        ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text("First time layer").clicked() {
+                layer = 0;
+            }
+            if ui
+                .button("◀")
+                .on_hover_text("Previous time layer")
+                .clicked()
+            {
+                layer = layer.saturating_sub(1);
+            }
+            let play = if timeline.playing { "⏸" } else { "▶" };
+            if ui.button(play).on_hover_text("Play or pause").clicked() {
+                timeline.playing = !timeline.playing;
+            }
+            if ui.button("▶|").on_hover_text("Next time layer").clicked() {
+                layer = (layer + 1).min(last);
+            }
+            ui.checkbox(&mut timeline.looping, "Loop");
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut timeline.frames_per_second)
+                    .range(0.1..=60.0)
+                    .speed(0.1),
+            );
+            ui.label("Frames per second");
+        });
+        ui.checkbox(&mut self.settings.fixed_color_range, "Fixed color range")
+            .on_hover_text(
+                "Check to use the range over all time layers, uncheck to rescale every frame",
+            );
+        if ui
+            .add_enabled(
+                self.export_frame.is_none(),
+                egui::Button::new("Export frames"),
+            )
+            .on_hover_text("Save every time layer as a numbered PNG in the frames folder")
+            .clicked()
+        {
+            timeline.playing = false;
+            layer = 0;
+            self.export_frame = Some(0);
+            self.export_requested = false;
+        }
+        if !self.export_status.is_empty() {
+            ui.label(&self.export_status);
+        }
+
+        if layer != timeline.current {
+            self.set_time_layer(layer);
+        }
+    }
+
+    fn edit_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Edit mesh", |ui| {
+            if ui
+                .checkbox(&mut self.edit_mode, "Edit mode")
+                .on_hover_text("Check to edit the mesh with the plot instead of placing streamline seeds")
+                .changed()
+            {
+                self.dragged_node = None;
+            }
+            ui.add_enabled_ui(self.edit_mode, |ui| {
+                ui.radio_value(&mut self.edit_tool, EditTool::MoveNode, "Drag nodes");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.edit_tool, EditTool::Material, "Assign material");
+                    egui::ComboBox::from_id_salt("edit material")
+                        .selected_text(self.edit_material.to_string())
+                        .show_ui(ui, |ui| {
+                            for material in &self.materials {
+                                ui.selectable_value(
+                                    &mut self.edit_material,
+                                    material.id,
+                                    format!("{}: {}", material.id, material.name),
+                                );
+                            }
+                        });
+                });
+                ui.radio_value(&mut self.edit_tool, EditTool::Dirichlet, "Toggle Dirichlet nodes");
+                ui.radio_value(&mut self.edit_tool, EditTool::Neumann, "Toggle Neumann edges");
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(self.history.undo_count() > 0, egui::Button::new("Undo"))
+                    .clicked()
+                {
+                    self.run_command(Command::Undo);
+                }
+                if ui
+                    .add_enabled(self.history.redo_count() > 0, egui::Button::new("Redo"))
+                    .clicked()
+                {
+                    self.run_command(Command::Redo);
+                }
+                ui.label(format!(
+                    "{} edits, {} undone",
+                    self.history.undo_count(),
+                    self.history.redo_count()
+                ));
+            });
+            if ui
+                .button("Refine mesh")
+                .on_hover_text("Split every element into four children, refining the boundary conditions and fields")
+                .clicked()
+            {
+                self.refine_mesh();
+            }
+            self.save_mesh_ui(ui);
+        });
+    }
+
+    fn save_mesh_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.save_folder)
+                .on_hover_text(
+                "Folder to write points, finite_elements, dirichlet, neumann, solution and time series files to",
+            );
+            if ui
+                .add_enabled(
+                    !self.save_folder.trim().is_empty(),
+                    egui::Button::new("Save"),
+                )
+                .clicked()
+            {
+                let saved = loaders::save_mesh(&self.mesh, &self.save_folder).and_then(|()| {
+                    match &self.timeline {
+                        Some(timeline) => loaders::save_timeline(timeline, &self.save_folder),
+                        None => Ok(()),
+                    }
+                });
+                self.save_status = match saved {
+                    Ok(()) => format!("Saved {}", self.save_folder),
+                    Err(e) => format!("Failed to save {}: {}", self.save_folder, e),
+                };
+            }
+        });
+        if !self.save_status.is_empty() {
+            ui.label(&self.save_status);
+        }
+    }
+
+    fn handle_edit(&mut self, ctx: &egui::Context, output: &PlotOutput) {
+        let (pressed, down) =
+            ctx.input(|i| (i.pointer.primary_pressed(), i.pointer.primary_down()));
+
+        if self.edit_tool == EditTool::MoveNode {
+            if pressed && output.hovered {
+                self.dragged_node = output
+                    .pointer
+                    .and_then(|p| editing::nearest_node(&self.mesh.points, p, output.pick_radius))
+                    .map(|node| (node, self.mesh.points[node]));
+            }
+            let Some((node, from)) = self.dragged_node else {
+                return;
+            };
+            if down {
+                if let Some(p) = output.pointer {
+                    self.mesh.points[node] = (p[0], p[1]);
+                    self.vector_cache = None;
+                }
+            } else {
+                self.dragged_node = None;
+                let to = self.mesh.points[node];
+                if to != from {
+                    self.history.record(Edit::MoveNode { node, from, to });
+                    self.mesh_edited();
+                }
+            }
+            return;
+        }
+
+        let Some(p) = output.clicked else {
+            return;
+        };
+        let edit = match self.edit_tool {
+            EditTool::Material => {
+                transfer::locate_element(&self.mesh, p).and_then(|(element, _)| {
+                    let from = materials::element_material(&self.mesh.elements[element])?;
+                    (from != self.edit_material).then_some(Edit::SetMaterial {
+                        element,
+                        from,
+                        to: self.edit_material,
+                    })
+                })
+            }
+            EditTool::Dirichlet => editing::nearest_node(&self.mesh.points, p, output.pick_radius)
+                .map(|node| Edit::toggle_dirichlet(&self.mesh, node)),
+            EditTool::Neumann => editing::nearest_edge(
+                &self.mesh.points,
+                &self.mesh.boundary_edges,
+                p,
+                output.pick_radius,
+            )
+            .map(|edge| Edit::toggle_neumann(&self.mesh, edge)),
+            EditTool::MoveNode => None,
+        };
+        if let Some(edit) = edit {
+            self.history.apply(&mut self.mesh, edit);
+            self.mesh_edited();
+        }
+    }
+
+    fn refine_mesh(&mut self) {
+        let refinement = match refinement::refine(&self.mesh) {
+            Ok(refinement) => refinement,
+            Err(e) => {
+                self.save_status = format!("Refinement failed: {}", e);
+                return;
+            }
+        };
+
+        let mut time_layers = 0;
+        if let Some(timeline) = &mut self.timeline {
+            for layer in &mut timeline.layers {
+                *layer = refinement.node_values(layer);
+            }
+            time_layers = timeline.frames_count();
+        }
+        self.save_status = format!(
+            "Refined to {} elements and {} nodes, {} time layers interpolated",
+            refinement.mesh.elements.len(),
+            refinement.mesh.points.len(),
+            time_layers
+        );
+        self.mesh = refinement.mesh;
+        self.history = editing::EditHistory::default();
+        self.dragged_node = None;
+        self.difference_norms = None;
+        self.mesh_edited();
+    }
+
+    fn renumber_mesh(&mut self) {
+        let Some(sparsity) = self.sparsity.take() else {
+            return;
+        };
+
+        if let Some(timeline) = &mut self.timeline {
+            for layer in &mut timeline.layers {
+                *layer = sparsity::permute(layer, &sparsity.renumbering);
+            }
+        }
+        self.mesh = sparsity::renumber(&self.mesh, &sparsity.renumbering);
+        self.history = editing::EditHistory::default();
+        self.dragged_node = None;
+        self.difference_norms = None;
+        self.save_status = format!(
+            "Renumbered, bandwidth {} -> {}",
+            sparsity.statistics.bandwidth, sparsity.renumbered.bandwidth
+        );
+        self.mesh_edited();
+    }
+
+    fn mesh_edited(&mut self) {
+        self.sparsity = None;
+        self.invalidate_display_caches();
+        self.update_reference_fields();
+        self.rebuild_streamlines();
+    }
+
+    fn comparison_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Comparison", |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.comparison_folder)
+                    .on_hover_text("Grid folder or mesh file in any registered format");
+                if ui.button("Load").clicked() {
+                    let folder = self.comparison_folder.clone();
+                    match self.readers.read(Path::new(&folder)) {
+                        Ok(comparison) => self.set_comparison(comparison, &folder),
+                        Err(e) => self.comparison_status = format!("Failed to load {}: {}", folder, e),
+                    }
+                }
+            });
+            ui.add_enabled_ui(self.comparison.is_some(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.compare_mode, CompareMode::Off, "Off");
+                    ui.radio_value(&mut self.compare_mode, CompareMode::SideBySide, "Side by side")
+                        .on_hover_text("Show both grids next to each other with linked pan and zoom");
+                    ui.radio_value(&mut self.compare_mode, CompareMode::Split, "Split")
+                        .on_hover_text("Overlay both grids and reveal the comparison right of a divider");
+                });
+                ui.add_enabled(
+                    self.compare_mode == CompareMode::Split,
+                    egui::Slider::new(&mut self.settings.split_position, 0.0..=1.0).text("Split position"),
+                );
+            });
+            if ui
+                .add_enabled(
+                    self.comparison.is_some(),
+                    egui::Button::new("Transfer comparison solution"),
+                )
+                .on_hover_text("Interpolate the comparison solution onto the grid nodes and add the difference as a field")
+                .clicked()
+            {
+                self.transfer_comparison();
+            }
+            if let Some(norms) = &self.difference_norms {
+                norms_grid(ui, "difference norms", norms, "Nodes outside comparison");
+            }
+            if !self.comparison_status.is_empty() {
+                ui.label(&self.comparison_status);
+            }
+        });
+    }
+
+    fn update_timeline(&mut self, ctx: &egui::Context) {
+        let (dt, screenshot) = ctx.input(|i| {
+            let screenshot = i.events.iter().find_map(|e| match e {
+                Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            });
+            (i.stable_dt, screenshot)
+        });
+
+        let next = self.timeline.as_mut().and_then(|timeline| {
+            if timeline.playing {
+                ctx.request_repaint();
+            }
+            timeline.advance(dt)
+        });
+        if let Some(next) = next {
+            self.set_time_layer(next);
+        }
+
+        let Some(frame) = self.export_frame else {
+            return;
+        };
+
+        if let (Some(image), true) = (screenshot, self.export_requested) {
+            let image = match self.plot_rect {
+                Some(rect) => image.region(&rect, Some(ctx.pixels_per_point())),
+                None => (*image).clone(),
+            };
+            match save_frame(&image, frame) {
+                Ok(path) => self.export_status = format!("Saved {}", path),
+                Err(e) => {
+                    self.export_status = format!("Export failed: {}", e);
+                    self.export_frame = None;
+                    return;
+                }
+            }
+
+            let frames_count = self.timeline.as_ref().map_or(0, |t| t.frames_count());
+            if frame + 1 < frames_count {
+                self.export_frame = Some(frame + 1);
+                self.export_requested = false;
+                self.set_time_layer(frame + 1);
+            } else {
+                self.export_frame = None;
+            }
+        } else if !self.export_requested {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            self.export_requested = true;
+        }
+
+        ctx.request_repaint();
+    }
+
+    fn triangle_visible(&self, mesh: &mesh::Mesh, triangle: usize) -> bool {
+        self.element_visible(&mesh.elements[mesh.triangle_elements[triangle]])
+    }
+
+    fn element_visible(&self, element: &[usize]) -> bool {
+        if !self.settings.restrict_to_visible_materials {
+            return true;
+        }
+
+        materials::element_material(element).is_none_or(|id| !self.hidden_materials.contains(&id))
+    }
+
+    fn show_statistics_window(&mut self, ctx: &egui::Context) {
+        let mesh = &self.mesh;
+        let statistics = statistics::material_statistics(
+            &mesh.points,
+            &mesh.triangles,
+            mesh.triangle_elements
+                .iter()
+                .map(|&element| materials::element_material(&mesh.elements[element])),
+            (0..mesh.triangles.len())
+                .map(|triangle| mesh.triangle_values(self.heatmap_field, triangle)),
+        );
+
+        egui::Window::new("Material statistics")
+            .open(&mut self.settings.show_statistics)
+            .show(ctx, |ui| {
+                egui::Grid::new("material statistics")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Material");
+                        ui.label("Area");
+                        ui.label("Integral");
+                        ui.label("Mean");
+                        ui.label("Min");
+                        ui.label("Max");
+                        ui.end_row();
+
+                        for entry in &statistics {
+                            let name = self
+                                .materials
+                                .iter()
+                                .find(|material| material.id == entry.id)
+                                .map_or_else(
+                                    || entry.id.to_string(),
+                                    |material| material.name.clone(),
+                                );
+                            ui.label(name);
+                            ui.label(format!("{:.6}", entry.area));
+                            ui.label(format!("{:.6}", entry.integral));
+                            ui.label(format!("{:.6}", entry.mean()));
+                            ui.label(format!("{:.6}", entry.minimum));
+                            ui.label(format!("{:.6}", entry.maximum));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn show_sparsity_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.settings.show_sparsity;
+        egui::Window::new("Sparsity pattern")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let sparsity = self.sparsity.get_or_insert_with(|| {
+                    sparsity::Sparsity::new(&self.mesh.elements, self.mesh.points.len())
+                });
+
+                egui::Grid::new("sparsity statistics")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label("Current");
+                        ui.label("Reverse Cuthill–McKee");
+                        ui.end_row();
+                        ui.label("Nonzeros");
+                        ui.label(sparsity.statistics.nonzeros.to_string());
+                        ui.label(sparsity.renumbered.nonzeros.to_string());
+                        ui.end_row();
+                        ui.label("Bandwidth");
+                        ui.label(sparsity.statistics.bandwidth.to_string());
+                        ui.label(sparsity.renumbered.bandwidth.to_string());
+                        ui.end_row();
+                        ui.label("Profile");
+                        ui.label(sparsity.statistics.profile.to_string());
+                        ui.label(sparsity.renumbered.profile.to_string());
+                        ui.end_row();
+                    });
+
+                let entries: Vec<[f64; 2]> = sparsity
+                    .adjacency
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(row, neighbors)| {
+                        neighbors
+                            .iter()
+                            .copied()
+                            .chain(std::iter::once(row))
+                            .map(move |column| [column as f64, -(row as f64)])
+                    })
+                    .collect();
+                egui_plot::Plot::new("Sparsity plot")
+                    .data_aspect(1.0)
+                    .height(300.0)
+                    .show(ui, |plot_ui| {
+                        plot_ui.points(
+                            egui_plot::Points::new(entries)
+                                .radius(1.0)
+                                .color(Color32::LIGHT_BLUE),
+                        );
+                    });
+
+                if ui
+                    .button("Apply reverse Cuthill–McKee")
+                    .on_hover_text(
+                        "Renumber the nodes to reduce the bandwidth of the global matrix",
+                    )
+                    .clicked()
+                {
+                    self.renumber_mesh();
+                }
+                self.save_mesh_ui(ui);
+            });
+        self.settings.show_sparsity = open;
+    }
+
+    fn show_convergence_window(&mut self, ctx: &egui::Context) {
+        let study = &mut self.convergence;
+        let readers = &self.readers;
+        egui::Window::new("Convergence study")
+            .open(&mut self.settings.show_convergence)
+            .show(ctx, |ui| {
+                ui.label("Grid folders, coarsest first");
+                ui.text_edit_multiline(&mut study.folders);
+                ui.horizontal(|ui| {
+                    ui.label("Analytic reference");
+                    ui.text_edit_singleline(&mut study.reference_expression)
+                        .on_hover_text(
+                        "Expression in x and y; leave empty to use the last grid as the reference",
+                    );
+                });
+                if ui.button("Run").clicked() {
+                    study.run(|folder| readers.read(Path::new(folder)));
+                }
+                if !study.status.is_empty() {
+                    ui.label(&study.status);
+                }
+                if study.levels.is_empty() {
+                    return;
+                }
+
+                ui.label(format!("Reference: {}", study.reference_folder));
+                let orders = study.orders();
+                egui::Grid::new("convergence levels")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Grid");
+                        ui.label("h");
+                        ui.label("L2 error");
+                        ui.label("Max error");
+                        ui.label("Order");
+                        ui.label("Nodes outside reference");
+                        ui.end_row();
+
+                        for (index, level) in study.levels.iter().enumerate() {
+                            ui.label(&level.folder);
+                            ui.label(format!("{:.6}", level.h));
+                            ui.label(format!("{:.6e}", level.l2));
+                            ui.label(format!("{:.6e}", level.maximum));
+                            match index.checked_sub(1).and_then(|i| orders[i]) {
+                                Some(order) => ui.label(format!("{:.3}", order)),
+                                None => ui.label("-"),
+                            };
+                            ui.label(level.missing.to_string());
+                            ui.end_row();
+                        }
+                    });
+
+                let log_points = |error: fn(&convergence::ConvergenceLevel) -> f64| {
+                    study
+                        .levels
+                        .iter()
+                        .filter(|level| level.h > 0.0 && error(level) > 0.0)
+                        .map(|level| [level.h.log10(), error(level).log10()])
+                        .collect::<Vec<[f64; 2]>>()
+                };
+                let l2_points = log_points(|level| level.l2);
+                let maximum_points = log_points(|level| level.maximum);
+
+                egui_plot::Plot::new("Convergence plot")
+                    .legend(Legend::default())
+                    .x_axis_label("log10 h")
+                    .y_axis_label("log10 error")
+                    .height(250.0)
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new(l2_points.clone()).name("L2 error"));
+                        plot_ui.points(
+                            egui_plot::Points::new(l2_points)
+                                .name("L2 error")
+                                .radius(4.0),
+                        );
+                        plot_ui.line(Line::new(maximum_points.clone()).name("Max error"));
+                        plot_ui.points(
+                            egui_plot::Points::new(maximum_points)
+                                .name("Max error")
+                                .radius(4.0),
+                        );
+                    });
+            });
+    }
+
+    fn update_vector_cache(&mut self) {
+        if !self.settings.layer(LayerKind::Vectors).visible {
+            return;
+        }
+        let settings = (
+            self.settings.vector_kind,
+            self.settings.scale_by_lambda,
+            self.settings.arrows_density,
+            self.settings.arrows_scale,
+        );
+        if self
+            .vector_cache
+            .as_ref()
+            .is_some_and(|cache| cache.settings == settings)
+        {
+            return;
+        }
+
+        let (mesh, comparison) = self.displayed_meshes();
+        self.vector_cache = Some(VectorCache {
+            settings,
+            primary: self.vector_arrows(mesh),
+            comparison: comparison.map(|comparison| self.vector_arrows(comparison)),
+        });
+    }
+
+    fn vector_arrows(&self, mesh: &mesh::Mesh) -> VectorArrows {
+        let solution = &mesh.fields[SOLUTION_FIELD].values;
+        let vectors: Vec<[f64; 2]> = mesh
+            .triangles
+            .iter()
+            .zip(&mesh.triangle_elements)
+            .map(|(triangle, &element)| {
+                let gradient = interpolation::triangle_gradient(&mesh.points, solution, triangle);
+                match self.settings.vector_kind {
+                    VectorKind::Gradient => gradient,
+                    VectorKind::Flux => {
+                        let lambda = if self.settings.scale_by_lambda {
+                            self.element_lambda(&mesh.elements[element])
+                        } else {
+                            1.0
+                        };
+                        [-lambda * gradient[0], -lambda * gradient[1]]
+                    }
+                }
+            })
+            .collect();
+
+        let mut origins: Vec<[f64; 2]> = Vec::new();
+        let mut tips: Vec<[f64; 2]> = Vec::new();
+        let max_length = vectors.iter().map(|v| v[0].hypot(v[1])).fold(0.0, f64::max);
+        if max_length == 0.0 || self.settings.arrows_density == 0 {
+            return VectorArrows { origins, tips };
+        }
+
+        let (min, max) = interpolation::bounding_box(&mesh.points);
+        let density = self.settings.arrows_density as usize;
+        let step = [
+            (max[0] - min[0]) / density as f64,
+            (max[1] - min[1]) / density as f64,
+        ];
+        let length = self.settings.arrows_scale as f64 * step[0].min(step[1]) / max_length;
+
+        for i in 0..density {
+            for j in 0..density {
+                let p = [
+                    min[0] + (i as f64 + 0.5) * step[0],
+                    min[1] + (j as f64 + 0.5) * step[1],
+                ];
+                if let Some((triangle, _)) =
+                    interpolation::locate_triangle(&mesh.points, &mesh.triangles, p)
+                {
+                    let v = vectors[triangle];
+                    origins.push(p);
+                    tips.push([p[0] + length * v[0], p[1] + length * v[1]]);
+                }
+            }
+        }
+
+        VectorArrows { origins, tips }
+    }
+
+    fn build_vector_field(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi,
+        primary: bool,
+        layer: &layers::Layer,
+    ) {
+        let Some(cache) = &self.vector_cache else {
+            return;
+        };
+        let arrows = if primary {
+            Some(&cache.primary)
+        } else {
+            cache.comparison.as_ref()
+        };
+        let Some(arrows) = arrows else {
+            return;
+        };
+
+        let name = match self.settings.vector_kind {
+            VectorKind::Gradient => "Gradient",
+            VectorKind::Flux => "Flux",
+        };
+        plot_ui.arrows(
+            Arrows::new(arrows.origins.clone(), arrows.tips.clone())
+                .color(layer.color32())
+                .name(name),
+        );
+    }
+
+    fn rebuild_streamlines(&mut self) {
+        let descent: Vec<[f64; 2]> = self
+            .mesh
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let gradient =
+                    interpolation::triangle_gradient(&self.mesh.points, self.solution(), triangle);
+                [-gradient[0], -gradient[1]]
+            })
+            .collect();
+        let ascent: Vec<[f64; 2]> = descent.iter().map(|v| [-v[0], -v[1]]).collect();
+
+        let (min, max) = interpolation::bounding_box(&self.mesh.points);
+        let step = self.settings.streamline_step * (max[0] - min[0]).hypot(max[1] - min[1]);
+
+        self.streamlines.clear();
+        for &seed in &self.streamline_seeds {
+            let mut fields = vec![&descent];
+            if self.settings.trace_both_directions {
+                fields.push(&ascent);
+            }
+
+            for directions in fields {
+                let polyline = streamlines::trace(
+                    &self.mesh.points,
+                    &self.mesh.triangles,
+                    &self.mesh.triangle_neighbors,
+                    directions,
+                    seed,
+                    step,
+                    self.settings.streamline_max_steps,
+                );
+                if polyline.len() > 1 {
+                    self.streamlines.push(polyline);
+                }
+            }
+        }
+    }
+
+    fn add_seed(&mut self, p: [f64; 2]) {
+        match self.settings.seed_mode {
+            SeedMode::Off => return,
+            SeedMode::Points => self.streamline_seeds.push(p),
+            SeedMode::Line => match self.seed_line_start.take() {
+                None => {
+                    self.seed_line_start = Some(p);
+                    return;
+                }
+                Some(start) => {
+                    let count = self.settings.seed_line_count.max(1) as usize;
+                    for i in 0..count {
+                        let t = if count == 1 {
+                            0.5
+                        } else {
+                            i as f64 / (count - 1) as f64
+                        };
+                        self.streamline_seeds.push([
+                            start[0] + t * (p[0] - start[0]),
+                            start[1] + t * (p[1] - start[1]),
+                        ]);
+                    }
+                }
+            },
+        }
+
+        self.rebuild_streamlines();
+    }
+
+    fn show_plot(
+        &self,
+        ui: &mut egui::Ui,
+        id: &str,
+        mesh: &mesh::Mesh,
+        primary: bool,
+        (scroll, pointer_down, modifiers): (Option<Vec2>, bool, egui::Modifiers),
+    ) -> PlotOutput {
+        let mut clicked = None;
+
+        let legend = if primary {
+            Legend::default()
+        } else {
+            Legend::default().position(egui_plot::Corner::RightTop)
+        };
+
+        let response = egui_plot::Plot::new(id)
+            .allow_zoom(false)
+            .allow_drag(false)
+            .allow_scroll(false)
+            .legend(legend)
+            .show_grid(self.settings.show_grid)
+            .show(ui, |plot_ui| {
+                if let (true, Some(bounds)) = (self.comparing().is_some(), self.linked_bounds) {
+                    plot_ui.set_plot_bounds(bounds);
+                }
+                if let Some(bounds) = self.requested_bounds {
+                    plot_ui.set_plot_bounds(bounds);
+                } else if self.reset_view_requested {
+                    plot_ui.set_auto_bounds(egui::Vec2b::TRUE);
+                }
+                if let Some(mut scroll) = scroll {
+                    if modifiers.ctrl == self.settings.ctrl_to_zoom {
+                        scroll = Vec2::splat(scroll.x + scroll.y);
+                        let mut zoom_factor = Vec2::from([
+                            (scroll.x * self.settings.zoom_speed / 10.0).exp(),
+                            (scroll.y * self.settings.zoom_speed / 10.0).exp(),
+                        ]);
+                        if self.settings.lock_x {
+                            zoom_factor.x = 1.0;
+                        }
+                        if self.settings.lock_y {
+                            zoom_factor.y = 1.0;
+                        }
+                        plot_ui.zoom_bounds_around_hovered(zoom_factor);
+                    } else {
+                        if modifiers.shift == self.settings.shift_to_horizontal {
+                            scroll = Vec2::new(scroll.y, scroll.x);
+                        }
+                        if self.settings.lock_x {
+                            scroll.x = 0.0;
+                        }
+                        if self.settings.lock_y {
+                            scroll.y = 0.0;
+                        }
+                        let delta_pos = self.settings.scroll_speed * scroll;
+                        plot_ui.translate_bounds(delta_pos);
+                    }
+                }
+                if plot_ui.response().hovered() && pointer_down {
+                    let mut pointer_translate = -plot_ui.pointer_coordinate_drag_delta();
+                    if self.settings.lock_x {
+                        pointer_translate.x = 0.0;
+                    }
+                    if self.settings.lock_y {
+                        pointer_translate.y = 0.0;
+                    }
+                    plot_ui.translate_bounds(pointer_translate);
+                }
+
+                let (heatmap_index, contour_index) = if primary {
+                    (self.heatmap_field, self.contour_field)
+                } else {
+                    (
+                        self.comparison_field(mesh, self.heatmap_field),
+                        self.comparison_field(mesh, self.contour_field),
+                    )
+                };
+                let range = self.value_range(self.heatmap_field);
+
+                if plot_ui.response().clicked() {
+                    if let Some(pointer) = plot_ui.pointer_coordinate() {
+                        clicked = Some([pointer.x, pointer.y]);
+                    }
+                }
+
+                for layer in self.settings.layers.iter().filter(|layer| layer.visible) {
+                    match layer.kind {
+                        LayerKind::Materials => self.build_materials(plot_ui, mesh, layer),
+                        LayerKind::Heatmap => {
+                            self.build_heatmap(plot_ui, mesh, heatmap_index, range, layer, None)
+                        }
+                        LayerKind::Bands => self.build_heatmap(
+                            plot_ui,
+                            mesh,
+                            heatmap_index,
+                            range,
+                            layer,
+                            Some(self.settings.isolines_count),
+                        ),
+                        LayerKind::Outline => {
+                            for element in &mesh.elements {
+                                let outline = mesh::element_outline(element);
+                                let vertices: Vec<[f64; 2]> = outline
+                                    .iter()
+                                    .chain(outline.first())
+                                    .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                                    .collect();
+                                plot_ui.line(
+                                    Line::new(vertices).color(layer.color32()).width(layer.size),
+                                );
+                            }
+                        }
+                        LayerKind::Contours => self.build_isolines(
+                            plot_ui,
+                            mesh,
+                            contour_index,
+                            self.value_range(self.contour_field),
+                            layer,
+                        ),
+                        LayerKind::Vectors => self.build_vector_field(plot_ui, primary, layer),
+                        LayerKind::Streamlines if primary => {
+                            for polyline in &self.streamlines {
+                                plot_ui.line(
+                                    Line::new(polyline.clone())
+                                        .name("Streamlines")
+                                        .color(layer.color32())
+                                        .width(layer.size),
+                                );
+                            }
+
+                            let seeds: PlotPoints = self
+                                .streamline_seeds
+                                .iter()
+                                .chain(&self.seed_line_start)
+                                .copied()
+                                .collect::<Vec<[f64; 2]>>()
+                                .into();
+                            plot_ui.points(
+                                egui_plot::Points::new(seeds)
+                                    .name("Streamline Seeds")
+                                    .radius(2.0 * layer.size)
+                                    .color(layer.color32()),
+                            );
+                        }
+                        LayerKind::Streamlines => {}
+                        LayerKind::Boundary | LayerKind::UncoveredBoundary => {
+                            let (edges, name) = if layer.kind == LayerKind::Boundary {
+                                (&mesh.boundary_edges, "Mesh Boundary")
+                            } else {
+                                (&mesh.uncovered_edges, "Uncovered Boundary")
+                            };
+                            for edge in edges {
+                                let edge_points: Vec<[f64; 2]> = edge
+                                    .iter()
+                                    .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                                    .collect();
+                                plot_ui.line(
+                                    Line::new(edge_points)
+                                        .name(name)
+                                        .color(layer.color32())
+                                        .width(layer.size),
+                                );
+                            }
+                        }
+                        LayerKind::Nodes => {
+                            let grid_points: PlotPoints = mesh
+                                .points
+                                .iter()
+                                .map(|&(x, y)| [x, y])
+                                .collect::<Vec<[f64; 2]>>()
+                                .into();
+                            plot_ui.points(
+                                egui_plot::Points::new(grid_points)
+                                    .radius(layer.size)
+                                    .color(layer.color32())
+                                    .name("Mesh Points"),
+                            );
+                        }
+                        LayerKind::Labels => {
+                            for (i, &(x, y)) in mesh.points.iter().enumerate() {
+                                plot_ui.text(
+                                    egui_plot::Text::new(
+                                        screen_offset(plot_ui, [x, y], Vec2::new(4.0, -4.0)),
+                                        RichText::new(format!("{}", i)).size(layer.size),
+                                    )
+                                    .anchor(egui::Align2::LEFT_BOTTOM)
+                                    .color(layer.color32()),
+                                );
+                            }
+                        }
+                        LayerKind::ElementLabels => {
+                            for (index, element) in mesh.elements.iter().enumerate() {
+                                let Some(centroid) = element_centroid(mesh, element) else {
+                                    continue;
+                                };
+                                plot_ui.text(
+                                    egui_plot::Text::new(
+                                        centroid.into(),
+                                        RichText::new(format!("{}", index)).size(layer.size),
+                                    )
+                                    .color(layer.color32()),
+                                );
+                            }
+                        }
+                        LayerKind::LocalNumbers => self.build_local_numbers(plot_ui, mesh, layer),
+                        LayerKind::Orientation => self.build_orientation(plot_ui, mesh, layer),
+                        LayerKind::Dirichlet => {
+                            let dirichlet_plot_points: PlotPoints = mesh
+                                .dirichlet
+                                .iter()
+                                .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                                .collect::<Vec<[f64; 2]>>()
+                                .into();
+                            plot_ui.points(
+                                egui_plot::Points::new(dirichlet_plot_points)
+                                    .name("Dirichlet")
+                                    .shape(egui_plot::MarkerShape::Circle)
+                                    .radius(layer.size)
+                                    .color(layer.color32()),
+                            );
+                        }
+                        LayerKind::Neumann | LayerKind::Robin => {
+                            let (edges, name) = if layer.kind == LayerKind::Neumann {
+                                (&mesh.neumann, "Neumann Edges")
+                            } else {
+                                (&mesh.robin, "Robin Edges")
+                            };
+                            for edge in edges.iter().filter(|edge| edge.len() >= 2) {
+                                let edge_points: Vec<_> = edge
+                                    .iter()
+                                    .map(|&i| [mesh.points[i].0, mesh.points[i].1])
+                                    .collect();
+                                plot_ui.line(
+                                    egui_plot::Line::new(edge_points.clone())
+                                        .name(name)
+                                        .color(layer.color32())
+                                        .width(layer.size),
+                                );
+
+                                plot_ui.points(
+                                    egui_plot::Points::new(edge_points)
+                                        .name(name)
+                                        .shape(egui_plot::MarkerShape::Circle)
+                                        .radius(2.5 * layer.size)
+                                        .color(layer.color32()),
+                                );
+                            }
+                        }
+                        LayerKind::BoundaryTypes => self.build_boundary_types(plot_ui, mesh, layer),
+                    }
+                }
+            });
+
+        PlotOutput {
+            rect: response.response.rect,
+            bounds: *response.transform.bounds(),
+            hovered: response.response.hovered(),
+            clicked,
+            pointer: response.response.hover_pos().map(|position| {
+                let value = response.transform.value_from_position(position);
+                [value.x, value.y]
+            }),
+            pick_radius: PICK_RADIUS * response.transform.dvalue_dpos()[0].abs(),
+        }
+    }
+}
+
+impl GridPlotter {
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let ctx = &ui.ctx().clone();
+        self.update_timeline(ctx);
+        self.handle_shortcuts(ctx);
+        if self.show_palette {
+            self.show_command_palette(ctx);
+        }
+        egui::SidePanel::left("options").show_inside(ui, |ui| {
+            ui.checkbox(&mut self.settings.lock_x, "Lock x axis").on_hover_text("Check to keep the X axis fixed, i.e., pan and zoom will only affect the Y axis");
+            ui.checkbox(&mut self.settings.lock_y, "Lock y axis").on_hover_text("Check to keep the Y axis fixed, i.e., pan and zoom will only affect the X axis");
+            ui.checkbox(&mut self.settings.ctrl_to_zoom, "Ctrl to zoom").on_hover_text("If unchecked, the behavior of the Ctrl key is inverted compared to the default controls\ni.e., scrolling the mouse without pressing any keys zooms the plot");
+            ui.checkbox(&mut self.settings.shift_to_horizontal, "Shift for horizontal scroll").on_hover_text("If unchecked, the behavior of the shift key is inverted compared to the default controls\ni.e., hold to scroll vertically, release to scroll horizontally");
+            ui.checkbox(&mut self.settings.show_grid,"Show grid").on_hover_text("Check to show grid on plot");
+            self.layers_ui(ui);
+            self.symmetry_ui(ui);
+            ui.collapsing("Materials", |ui| {
+                for material in &mut self.materials {
+                    ui.horizontal(|ui| {
+                        let mut visible = !self.hidden_materials.contains(&material.id);
+                        if ui.checkbox(&mut visible, "").on_hover_text("Check to show this material").changed() {
+                            if visible {
+                                self.hidden_materials.remove(&material.id);
+                            } else {
+                                self.hidden_materials.insert(material.id);
+                            }
+                        }
+                        ui.color_edit_button_srgb(&mut material.color);
+                        ui.label(format!("{}: {}", material.id, material.name));
+                        if let Some(coefficients) = material.material {
+                            ui.label(format!("λ = {}, γ = {}", coefficients.lambda, coefficients.gamma));
+                        }
+                    });
+                }
+            });
+            ui.checkbox(&mut self.settings.restrict_to_visible_materials, "Restrict field to visible materials").on_hover_text("Check to draw the heatmap and contours only over visible materials");
+            ui.checkbox(&mut self.settings.show_statistics, "Show material statistics").on_hover_text("Check to show solution statistics per material");
+            self.boundary_ui(ui);
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.settings.vector_kind, VectorKind::Gradient, "Gradient");
+                ui.radio_value(&mut self.settings.vector_kind, VectorKind::Flux, "Flux");
+            });
+            ui.checkbox(&mut self.settings.scale_by_lambda, "Scale flux by lambda").on_hover_text("Check to multiply the flux by the element material lambda");
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut self.settings.arrows_density).range(1..=200));
+                ui.label("Arrows density").on_hover_text("Number of arrow samples along each axis");
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.settings.arrows_scale)
+                        .range(0.1..=10.0)
+                        .speed(0.1),
+                );
+                ui.label("Arrows scale").on_hover_text("Length of the longest arrow relative to the sample spacing");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Seeds");
+                ui.radio_value(&mut self.settings.seed_mode, SeedMode::Off, "Off").on_hover_text("Clicks on the plot do not place seeds");
+                ui.radio_value(&mut self.settings.seed_mode, SeedMode::Points, "Points").on_hover_text("Every click on the plot places a seed");
+                ui.radio_value(&mut self.settings.seed_mode, SeedMode::Line, "Line").on_hover_text("Two clicks on the plot place a line of seeds");
+            });
+            let mut retrace = false;
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut self.settings.seed_line_count).range(1..=200));
+                ui.label("Seeds on line").on_hover_text("Number of seeds placed along a seed line");
+            });
+            ui.horizontal(|ui| {
+                retrace |= ui
+                    .add(
+                        DragValue::new(&mut self.settings.streamline_step)
+                            .range(0.0001..=0.1)
+                            .speed(0.001),
+                    )
+                    .changed();
+                ui.label("Streamline step").on_hover_text("Integration step relative to the mesh diagonal");
+            });
+            ui.horizontal(|ui| {
+                retrace |= ui
+                    .add(DragValue::new(&mut self.settings.streamline_max_steps).range(1..=100000))
+                    .changed();
+                ui.label("Streamline max steps");
+            });
+            retrace |= ui.checkbox(&mut self.settings.trace_both_directions, "Trace both directions").on_hover_text("Check to also trace streamlines upstream of the seeds").changed();
+            if ui.button("Clear seeds").clicked() {
+                self.streamline_seeds.clear();
+                self.seed_line_start = None;
+                retrace = true;
+            }
+            if retrace {
+                self.rebuild_streamlines();
+            }
+            ui.checkbox(&mut self.settings.show_triangles, "Show triangulate grid").on_hover_text("Check to show triangulate grid");
+            self.fields_ui(ui);
+            self.timeline_ui(ui);
+            self.comparison_ui(ui);
+            self.edit_ui(ui);
+            self.reference_ui(ui);
+            self.views_ui(ui);
+            self.shortcuts_ui(ui);
+            self.settings_ui(ui);
+            ui.checkbox(&mut self.settings.show_convergence, "Show convergence study").on_hover_text("Check to compute error norms over a sequence of refined grids");
+            ui.checkbox(&mut self.settings.show_sparsity, "Show sparsity pattern").on_hover_text("Check to show the global matrix portrait built from element connectivity");
+            ui.horizontal(|ui| {
+                ui.label("Isolines amount");
+                integer_edit_field(ui, &mut self.settings.isolines_count);
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.settings.zoom_speed)
+                        .range(0.1..=2.0)
+                        .speed(0.1),
+                );
+                ui.label("Zoom speed").on_hover_text("How fast to zoom in and out with the mouse wheel");
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.settings.scroll_speed)
+                        .range(0.1..=100.0)
+                        .speed(0.1),
+                );
+                ui.label("Scroll speed").on_hover_text("How fast to pan with the mouse wheel");
+            });
+        });
+        if self.settings.show_statistics {
+            self.show_statistics_window(ctx);
+        }
+        if self.settings.show_convergence {
+            self.show_convergence_window(ctx);
+        }
+        if self.settings.show_sparsity {
+            self.show_sparsity_window(ctx);
+        }
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            let input = ui.input(|i| {
+                let scroll = i.events.iter().find_map(|e| match e {
+                    Event::MouseWheel {
+                        unit: _,
+                        delta,
+                        modifiers: _,
+                    } => Some(*delta),
+                    _ => None,
+                });
+                let panning = i.pointer.primary_down() && self.dragged_node.is_none();
+                (scroll, panning, i.modifiers)
+            });
+
+            self.update_symmetry_cache();
+            self.update_vector_cache();
+            let (mesh, comparison) = self.displayed_meshes();
+
+            let outputs = match (comparison, self.compare_mode) {
+                (Some(comparison), CompareMode::SideBySide) => {
+                    let mut outputs = Vec::new();
+                    ui.columns(2, |columns| {
+                        outputs.push(self.show_plot(
+                            &mut columns[0],
+                            "Grid plotter",
+                            mesh,
+                            true,
+                            input,
+                        ));
+                        outputs.push(self.show_plot(
+                            &mut columns[1],
+                            "Comparison plotter",
+                            comparison,
+                            false,
+                            input,
+                        ));
+                    });
+                    outputs
+                }
+                (Some(comparison), CompareMode::Split) => {
+                    let rect = ui.available_rect_before_wrap();
+                    let split_x = rect.left() + rect.width() * self.settings.split_position;
+                    let mut left = ui.new_child(egui::UiBuilder::new().max_rect(rect));
+                    let primary = self.show_plot(&mut left, "Grid plotter", mesh, true, input);
+                    let mut right = ui.new_child(egui::UiBuilder::new().max_rect(rect));
+                    right.set_clip_rect(egui::Rect::from_min_max(
+                        egui::pos2(split_x, rect.top()),
+                        rect.max,
+                    ));
+                    let secondary =
+                        self.show_plot(&mut right, "Comparison plotter", comparison, false, input);
+                    ui.painter().vline(
+                        split_x,
+                        rect.y_range(),
+                        egui::Stroke::new(2.0, Color32::WHITE),
+                    );
+                    vec![primary, secondary]
+                }
+                _ => vec![self.show_plot(ui, "Grid plotter", mesh, true, input)],
+            };
+
+            if let Some(bounds) = self.requested_bounds.take() {
+                self.linked_bounds = Some(bounds);
+            } else if std::mem::take(&mut self.reset_view_requested) {
+                self.linked_bounds = None;
+            } else if outputs.len() > 1 {
+                let leader = outputs
+                    .iter()
+                    .find(|output| output.hovered)
+                    .unwrap_or(&outputs[0]);
+                if self.linked_bounds != Some(leader.bounds) {
+                    self.linked_bounds = Some(leader.bounds);
+                    ctx.request_repaint();
+                }
+            }
+            self.current_bounds = Some(outputs[0].bounds);
+            self.plot_rect = outputs
+                .iter()
+                .map(|output| output.rect)
+                .reduce(|a, b| a.union(b));
+
+            if self.edit_mode {
+                self.handle_edit(ctx, &outputs[0]);
+            } else if let Some(p) = outputs[0].clicked {
+                self.add_seed(p);
+            }
+        });
+
+        self.save_settings_when_idle(ctx);
+    }
+}
+
+fn element_centroid(mesh: &mesh::Mesh, element: &[usize]) -> Option<[f64; 2]> {
+    let outline = mesh::element_outline(element);
+    if outline.is_empty() {
+        return None;
+    }
+    let count = outline.len() as f64;
+    let (x, y) = outline.iter().fold((0.0, 0.0), |(x, y), &node| {
+        (x + mesh.points[node].0, y + mesh.points[node].1)
+    });
+    Some([x / count, y / count])
+}
+
+fn screen_offset(
+    plot_ui: &egui_plot::PlotUi,
+    [x, y]: [f64; 2],
+    offset: Vec2,
+) -> egui_plot::PlotPoint {
+    plot_ui.plot_from_screen(plot_ui.screen_from_plot([x, y].into()) + offset)
+}
+
+fn integer_edit_field(ui: &mut egui::Ui, value: &mut u16) -> egui::Response {
+    let mut tmp_value = format!("{}", value);
+    let res = ui.text_edit_singleline(&mut tmp_value);
+    if let Ok(result) = tmp_value.parse() {
+        *value = result;
+    }
+    res
+}
+
+fn field_selector(ui: &mut egui::Ui, id: &str, fields: &[Field], selected: &mut usize) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(&fields[*selected].name)
+        .show_ui(ui, |ui| {
+            for (index, field) in fields.iter().enumerate() {
+                ui.selectable_value(selected, index, &field.name);
+            }
+        });
+}
+
+fn norms_grid(ui: &mut egui::Ui, id: &str, norms: &transfer::DifferenceNorms, missing_label: &str) {
+    egui::Grid::new(id).show(ui, |ui| {
+        ui.label("Max difference");
+        ui.label(format!("{:.6e}", norms.maximum));
+        ui.end_row();
+        ui.label("L2 difference");
+        ui.label(format!("{:.6e}", norms.l2));
+        ui.end_row();
+        ui.label("Relative L2");
+        ui.label(format!("{:.6e}", norms.relative_l2));
+        ui.end_row();
+        if norms.missing > 0 {
+            ui.label(missing_label);
+            ui.label(norms.missing.to_string());
+            ui.end_row();
+        }
+    });
+}
+
+fn save_frame(image: &egui::ColorImage, frame: usize) -> image::ImageResult<String> {
+    std::fs::create_dir_all("frames")?;
+    let path = format!("frames/frame_{:04}.png", frame);
+    image::save_buffer(
+        &path,
+        image.as_raw(),
+        image.size[0] as u32,
+        image.size[1] as u32,
+        image::ExtendedColorType::Rgba8,
+    )?;
+    Ok(path)
+}
+
+impl eframe::App for GridPlotter {
+    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none())
+            .show(ctx, |ui| self.show(ui));
+    }
+}
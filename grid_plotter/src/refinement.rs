@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use crate::boundary::{self, BoundaryValue, Conditions, EdgeValues};
+use crate::field::{Field, FieldLocation};
+use crate::materials;
+use crate::mesh::Mesh;
+use crate::transfer;
+
+const QUADRATIC: [(usize, usize, usize); 9] = [
+    (0, 0, 0),
+    (1, 1, 0),
+    (2, 2, 0),
+    (3, 2, 1),
+    (4, 2, 2),
+    (5, 1, 2),
+    (6, 0, 2),
+    (7, 0, 1),
+    (9, 1, 1),
+];
+
+const LINEAR: [(usize, usize, usize); 4] = [(0, 0, 0), (1, 1, 0), (2, 1, 1), (3, 0, 1)];
+
+#[derive(PartialEq, Eq, Hash)]
+enum NodeKey {
+    Old(usize),
+    Edge(usize, usize),
+    Interior(usize, usize, usize),
+}
+
+pub struct Refinement {
+    pub mesh: Mesh,
+    weights: Vec<Vec<(usize, f64)>>,
+}
+
+impl Refinement {
+    pub fn node_values(&self, values: &[f64]) -> Vec<f64> {
+        self.weights
+            .iter()
+            .map(|weights| {
+                weights
+                    .iter()
+                    .map(|&(node, weight)| weight * values[node])
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+type Lattice = &'static [(usize, usize, usize)];
+
+fn lattice(element: &[usize]) -> Option<(usize, Lattice)> {
+    match element.len() {
+        10 => Some((2, &QUADRATIC)),
+        9 => Some((2, &QUADRATIC[..8])),
+        5 => Some((1, &LINEAR)),
+        _ => None,
+    }
+}
+
+fn edge_key(a: usize, b: usize) -> NodeKey {
+    NodeKey::Edge(a.min(b), a.max(b))
+}
+
+pub fn refine(mesh: &Mesh) -> Result<Refinement, String> {
+    let mut points = mesh.points.clone();
+    let mut weights: Vec<Vec<(usize, f64)>> = (0..points.len()).map(|i| vec![(i, 1.0)]).collect();
+    let mut nodes: HashMap<NodeKey, usize> = HashMap::new();
+    let mut elements = Vec::with_capacity(4 * mesh.elements.len());
+    let mut parents = Vec::with_capacity(4 * mesh.elements.len());
+
+    for (index, element) in mesh.elements.iter().enumerate() {
+        let (order, table) = lattice(element)
+            .ok_or_else(|| format!("Element {} has an unsupported layout", index))?;
+        let size = 2 * order;
+        let old_node = |i: usize, j: usize| {
+            table
+                .iter()
+                .find(|&&(_, a, b)| (a, b) == (i, j))
+                .map(|&(entry, _, _)| element[entry])
+        };
+
+        let mut refined_node = |i: usize, j: usize| -> usize {
+            let on_side = i == 0 || i == size || j == 0 || j == size;
+            let key = match (i % 2, j % 2) {
+                (0, 0) => old_node(i / 2, j / 2).map(NodeKey::Old),
+                (1, 0) if on_side => Some(edge_key(
+                    old_node(i / 2, j / 2).unwrap(),
+                    old_node(i / 2 + 1, j / 2).unwrap(),
+                )),
+                (0, 1) if on_side => Some(edge_key(
+                    old_node(i / 2, j / 2).unwrap(),
+                    old_node(i / 2, j / 2 + 1).unwrap(),
+                )),
+                _ => None,
+            }
+            .unwrap_or(NodeKey::Interior(index, i, j));
+            if let NodeKey::Old(node) = key {
+                return node;
+            }
+
+            *nodes.entry(key).or_insert_with(|| {
+                let xi = -1.0 + 2.0 * i as f64 / size as f64;
+                let eta = -1.0 + 2.0 * j as f64 / size as f64;
+                let node_weights: Vec<(usize, f64)> = transfer::shape_functions(element, xi, eta)
+                    .iter()
+                    .map(|&(entry, value)| (element[entry], value))
+                    .collect();
+                let point = node_weights
+                    .iter()
+                    .fold((0.0, 0.0), |(x, y), &(node, value)| {
+                        (
+                            x + value * mesh.points[node].0,
+                            y + value * mesh.points[node].1,
+                        )
+                    });
+                points.push(point);
+                weights.push(node_weights);
+                points.len() - 1
+            })
+        };
+
+        let material = materials::element_material(element).unwrap_or(0);
+        for cj in 0..2 {
+            for ci in 0..2 {
+                let mut child = vec![0; element.len()];
+                for &(entry, i, j) in table {
+                    child[entry] = refined_node(ci * order + i, cj * order + j);
+                }
+                materials::set_element_material(&mut child, material);
+                elements.push(child);
+                parents.push(index);
+            }
+        }
+    }
+
+    let side_nodes = |edge: &[usize]| -> Vec<usize> {
+        let mut refined = vec![edge[0]];
+        for pair in edge.windows(2) {
+            refined.push(nodes[&edge_key(pair[0], pair[1])]);
+            refined.push(pair[1]);
+        }
+        refined
+    };
+
+    let mut dirichlet = mesh.dirichlet.clone();
+    let mut dirichlet_values = mesh.conditions.dirichlet.clone();
+    for edge in &mesh.boundary_edges {
+        if edge.iter().all(|node| mesh.dirichlet.contains(node)) {
+            let refined = side_nodes(edge);
+            for (k, &node) in refined.iter().enumerate() {
+                if dirichlet.contains(&node) {
+                    continue;
+                }
+                dirichlet.push(node);
+                let ends = (
+                    mesh.conditions.dirichlet.get(&refined[k - 1]),
+                    mesh.conditions.dirichlet.get(&refined[k + 1]),
+                );
+                if let (Some(before), Some(after)) = ends {
+                    let values = before
+                        .values
+                        .iter()
+                        .zip(&after.values)
+                        .map(|(a, b)| (a + b) / 2.0)
+                        .collect();
+                    dirichlet_values.insert(
+                        node,
+                        BoundaryValue {
+                            type_id: before.type_id,
+                            values,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    let boundary: HashMap<(usize, usize), &Vec<usize>> = mesh
+        .boundary_edges
+        .iter()
+        .map(|edge| (boundary::edge_key(edge), edge))
+        .collect();
+    let split_edges = |edges: &[Vec<usize>],
+                       values: &EdgeValues|
+     -> Result<(Vec<Vec<usize>>, EdgeValues), String> {
+        let mut refined_edges = Vec::with_capacity(2 * edges.len());
+        let mut refined_values = EdgeValues::new();
+        for edge in edges {
+            if edge.len() < 2 {
+                refined_edges.push(edge.clone());
+                continue;
+            }
+            let key = boundary::edge_key(edge);
+            let side = boundary.get(&key).ok_or_else(|| {
+                format!(
+                    "Boundary edge {} {} is not on the mesh boundary",
+                    edge[0],
+                    edge[edge.len() - 1]
+                )
+            })?;
+            let mut side = side.to_vec();
+            if side[0] != edge[0] {
+                side.reverse();
+            }
+
+            let refined = side_nodes(&side);
+            let half = refined.len() / 2;
+            for child in [&refined[..=half], &refined[half..]] {
+                let child = if edge.len() == 2 {
+                    vec![child[0], child[child.len() - 1]]
+                } else {
+                    child.to_vec()
+                };
+                if let Some(value) = values.get(&key) {
+                    refined_values.insert(boundary::edge_key(&child), value.clone());
+                }
+                refined_edges.push(child);
+            }
+        }
+        Ok((refined_edges, refined_values))
+    };
+    let (neumann, neumann_values) = split_edges(&mesh.neumann, &mesh.conditions.neumann)?;
+    let (robin, robin_values) = split_edges(&mesh.robin, &mesh.conditions.robin)?;
+
+    let mut refinement = Refinement {
+        mesh: Mesh::default(),
+        weights,
+    };
+    let fields = mesh
+        .fields
+        .iter()
+        .map(|field| {
+            let values = match field.location {
+                FieldLocation::Node => refinement.node_values(&field.values),
+                FieldLocation::Element => parents.iter().map(|&p| field.values[p]).collect(),
+            };
+            Field::new(&field.name, &field.source, values)
+        })
+        .collect();
+
+    let mut refined = Mesh::new(points, elements, dirichlet, neumann, fields);
+    refined.robin = robin;
+    refined.conditions = Conditions {
+        dirichlet: dirichlet_values,
+        neumann: neumann_values,
+        robin: robin_values,
+    };
+    refined.update_uncovered_edges();
+    refinement.mesh = refined;
+    Ok(refinement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loaders;
+    use crate::mesh::{element_nodes, element_outline};
+    use std::collections::HashSet;
+
+    const GRIDS: [&str; 3] = ["grid", "grid/hole", "grid/test_grid"];
+
+    fn load(name: &str) -> Mesh {
+        loaders::load_mesh(&format!("{}/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+    }
+
+    fn outline_segments(mesh: &Mesh) -> HashSet<(usize, usize)> {
+        mesh.elements
+            .iter()
+            .flat_map(|element| {
+                let outline = element_outline(element);
+                let next = outline.iter().cycle().skip(1);
+                outline
+                    .iter()
+                    .zip(next)
+                    .map(|(&a, &b)| (a.min(b), a.max(b)))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn refinement_counts_nodes_and_elements() {
+        for name in GRIDS {
+            let mesh = load(name);
+            let refined = refine(&mesh).unwrap().mesh;
+            let interior_nodes: usize = mesh
+                .elements
+                .iter()
+                .map(|element| match element.len() {
+                    10 => 8,
+                    9 => 5,
+                    _ => 1,
+                })
+                .sum();
+
+            assert_eq!(refined.elements.len(), 4 * mesh.elements.len(), "{}", name);
+            assert_eq!(
+                refined.points.len(),
+                mesh.points.len() + outline_segments(&mesh).len() + interior_nodes,
+                "{}",
+                name
+            );
+            assert!(refined
+                .elements
+                .iter()
+                .zip(mesh.elements.iter().flat_map(|e| [e; 4]))
+                .all(|(child, parent)| child.len() == parent.len()
+                    && materials::element_material(child) == materials::element_material(parent)));
+        }
+    }
+
+    #[test]
+    fn neighbours_share_mid_edge_nodes() {
+        for name in GRIDS {
+            let mesh = load(name);
+            let refined = refine(&mesh).unwrap().mesh;
+            let used: HashSet<usize> = refined
+                .elements
+                .iter()
+                .flat_map(|element| element_nodes(element))
+                .collect();
+            assert!(
+                (mesh.points.len()..refined.points.len()).all(|node| used.contains(&node)),
+                "{}",
+                name
+            );
+
+            // grid/hole keeps unused centre nodes where the serendipity centres land.
+            let distinct: HashSet<(u64, u64)> = used
+                .iter()
+                .map(|&node| refined.points[node])
+                .map(|(x, y)| (x.to_bits(), y.to_bits()))
+                .collect();
+            assert_eq!(distinct.len(), used.len(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn boundary_sets_follow_refinement() {
+        for name in GRIDS {
+            let mesh = load(name);
+            let refined = refine(&mesh).unwrap().mesh;
+            let boundary_nodes: HashSet<usize> =
+                refined.boundary_edges.iter().flatten().copied().collect();
+            let dirichlet: HashSet<usize> = refined.dirichlet.iter().copied().collect();
+            let added: usize = mesh
+                .boundary_edges
+                .iter()
+                .filter(|edge| edge.iter().all(|node| mesh.dirichlet.contains(node)))
+                .map(|edge| edge.len() - 1)
+                .sum();
+
+            assert_eq!(
+                refined.dirichlet[..mesh.dirichlet.len()],
+                mesh.dirichlet[..]
+            );
+            assert_eq!(dirichlet.len(), refined.dirichlet.len(), "{}", name);
+            assert_eq!(
+                refined.dirichlet.len(),
+                mesh.dirichlet.len() + added,
+                "{}",
+                name
+            );
+            assert!(added > 0, "{}", name);
+            assert!(dirichlet.is_subset(&boundary_nodes), "{}", name);
+
+            let refined_sides: HashSet<(usize, usize)> = refined
+                .boundary_edges
+                .iter()
+                .flat_map(|edge| edge.windows(2))
+                .map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1])))
+                .collect();
+            assert_eq!(refined.neumann.len(), 2 * mesh.neumann.len(), "{}", name);
+            for (parent, children) in mesh.neumann.iter().zip(refined.neumann.chunks(2)) {
+                assert_eq!(children[0][0], parent[0]);
+                assert_eq!(children[1][children[1].len() - 1], parent[parent.len() - 1]);
+                assert_eq!(children[0][children[0].len() - 1], children[1][0]);
+                for child in children {
+                    assert!(refined_sides.contains(&boundary::edge_key(child)));
+                }
+            }
+        }
+        assert!(!load("grid/test_grid").neumann.is_empty());
+    }
+}
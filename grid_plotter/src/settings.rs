@@ -0,0 +1,179 @@
+use crate::commands::{self, Command};
+use crate::layers::{self, Layer, LayerKind};
+use crate::symmetry::Symmetry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+pub fn settings_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| std::env::var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config.join("grid_plotter").join(SETTINGS_FILE))
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum VectorKind {
+    Gradient,
+    Flux,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum SeedMode {
+    Off,
+    Points,
+    Line,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub lock_x: bool,
+    pub lock_y: bool,
+    pub ctrl_to_zoom: bool,
+    pub shift_to_horizontal: bool,
+    pub show_triangles: bool,
+    pub zoom_speed: f32,
+    pub scroll_speed: f32,
+    pub show_grid: bool,
+    pub isolines_count: u16,
+    pub show_statistics: bool,
+    pub show_convergence: bool,
+    pub show_sparsity: bool,
+    pub restrict_to_visible_materials: bool,
+    pub vector_kind: VectorKind,
+    pub scale_by_lambda: bool,
+    pub arrows_density: u16,
+    pub arrows_scale: f32,
+    pub seed_mode: SeedMode,
+    pub seed_line_count: u16,
+    pub streamline_step: f64,
+    pub streamline_max_steps: usize,
+    pub trace_both_directions: bool,
+    pub average_cell_fields: bool,
+    pub fixed_color_range: bool,
+    pub split_position: f32,
+    pub layers: Vec<Layer>,
+    pub symmetry: Symmetry,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            lock_x: false,
+            lock_y: false,
+            ctrl_to_zoom: false,
+            shift_to_horizontal: false,
+            show_triangles: false,
+            zoom_speed: 1.0,
+            scroll_speed: 1.0,
+            show_grid: true,
+            isolines_count: 10,
+            show_statistics: false,
+            show_convergence: false,
+            show_sparsity: false,
+            restrict_to_visible_materials: false,
+            vector_kind: VectorKind::Flux,
+            scale_by_lambda: true,
+            arrows_density: 20,
+            arrows_scale: 1.0,
+            seed_mode: SeedMode::Off,
+            seed_line_count: 10,
+            streamline_step: 0.01,
+            streamline_max_steps: 1000,
+            trace_both_directions: false,
+            average_cell_fields: false,
+            fixed_color_range: true,
+            split_position: 0.5,
+            layers: layers::default_layers(),
+            symmetry: Symmetry::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn layer(&self, kind: LayerKind) -> &Layer {
+        self.layers
+            .iter()
+            .find(|layer| layer.kind == kind)
+            .expect("settings contain every layer")
+    }
+
+    pub fn toggle_layer(&mut self, kind: LayerKind) {
+        if let Some(layer) = self.layers.iter_mut().find(|layer| layer.kind == kind) {
+            layer.visible ^= true;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub struct View {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
+}
+
+impl View {
+    pub fn parse(text: &str) -> Option<Self> {
+        let values: Vec<f64> = text
+            .split(',')
+            .map(|value| value.trim().parse())
+            .collect::<Result<_, _>>()
+            .ok()?;
+        match values[..] {
+            [x_min, y_min, x_max, y_max] if x_min < x_max && y_min < y_max => Some(Self {
+                min: [x_min, y_min],
+                max: [x_max, y_max],
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SettingsFile {
+    pub current: Settings,
+    pub presets: BTreeMap<String, Settings>,
+    pub views: BTreeMap<String, View>,
+    pub bindings: BTreeMap<Command, String>,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        Self {
+            current: Settings::default(),
+            presets: BTreeMap::new(),
+            views: BTreeMap::new(),
+            bindings: commands::default_bindings(),
+        }
+    }
+}
+
+impl SettingsFile {
+    pub fn load(filename: &str) -> io::Result<Self> {
+        let reader = io::BufReader::new(File::open(filename)?);
+        let mut file: Self = serde_json::from_reader(reader)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for (command, shortcut) in commands::default_bindings() {
+            file.bindings.entry(command).or_insert(shortcut);
+        }
+        layers::complete_layers(&mut file.current.layers);
+        for preset in file.presets.values_mut() {
+            layers::complete_layers(&mut preset.layers);
+        }
+        Ok(file)
+    }
+
+    pub fn save(&self, filename: &str) -> io::Result<()> {
+        if let Some(folder) = Path::new(filename).parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+        let writer = io::BufWriter::new(File::create(filename)?);
+        serde_json::to_writer_pretty(writer, self).map_err(io::Error::other)
+    }
+}
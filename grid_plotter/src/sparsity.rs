@@ -0,0 +1,308 @@
+use crate::boundary::{Conditions, EdgeValues};
+use crate::field::{Field, FieldLocation};
+use crate::materials;
+use crate::mesh::{self, Mesh};
+
+#[derive(Clone, Copy)]
+pub struct Statistics {
+    pub nonzeros: usize,
+    pub bandwidth: usize,
+    pub profile: usize,
+}
+
+pub struct Sparsity {
+    pub adjacency: Vec<Vec<usize>>,
+    pub statistics: Statistics,
+    pub renumbering: Vec<usize>,
+    pub renumbered: Statistics,
+}
+
+impl Sparsity {
+    pub fn new(elements: &[Vec<usize>], nodes_count: usize) -> Self {
+        let adjacency = adjacency(elements, nodes_count);
+        let identity: Vec<usize> = (0..nodes_count).collect();
+        let renumbering = reverse_cuthill_mckee(&adjacency);
+
+        Self {
+            statistics: statistics(&adjacency, &identity),
+            renumbered: statistics(&adjacency, &renumbering),
+            adjacency,
+            renumbering,
+        }
+    }
+}
+
+pub fn adjacency(elements: &[Vec<usize>], nodes_count: usize) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![Vec::new(); nodes_count];
+    for element in elements {
+        let nodes = mesh::element_nodes(element);
+        for &a in &nodes {
+            adjacency[a].extend(nodes.iter().copied().filter(|&b| b != a));
+        }
+    }
+    for neighbors in &mut adjacency {
+        neighbors.sort_unstable();
+        neighbors.dedup();
+    }
+    adjacency
+}
+
+pub fn statistics(adjacency: &[Vec<usize>], new_index: &[usize]) -> Statistics {
+    let mut first_column: Vec<usize> = (0..adjacency.len()).collect();
+    let mut nonzeros = adjacency.len();
+    let mut bandwidth = 0;
+
+    for (node, neighbors) in adjacency.iter().enumerate() {
+        let row = new_index[node];
+        nonzeros += neighbors.len();
+        for &neighbor in neighbors {
+            let column = new_index[neighbor];
+            bandwidth = bandwidth.max(row.abs_diff(column));
+            first_column[row] = first_column[row].min(column);
+        }
+    }
+
+    Statistics {
+        nonzeros,
+        bandwidth,
+        profile: first_column
+            .iter()
+            .enumerate()
+            .map(|(row, &column)| row - column)
+            .sum(),
+    }
+}
+
+fn level_structure(adjacency: &[Vec<usize>], start: usize) -> Vec<Vec<usize>> {
+    let mut visited = vec![false; adjacency.len()];
+    visited[start] = true;
+    let mut levels = vec![vec![start]];
+
+    loop {
+        let mut next = Vec::new();
+        for &node in levels.last().unwrap() {
+            for &neighbor in &adjacency[node] {
+                if !visited[neighbor] {
+                    visited[neighbor] = true;
+                    next.push(neighbor);
+                }
+            }
+        }
+        if next.is_empty() {
+            return levels;
+        }
+        levels.push(next);
+    }
+}
+
+fn pseudo_peripheral_node(adjacency: &[Vec<usize>], start: usize) -> usize {
+    let mut node = start;
+    let mut eccentricity = level_structure(adjacency, node).len();
+
+    loop {
+        let levels = level_structure(adjacency, node);
+        let candidate = *levels
+            .last()
+            .unwrap()
+            .iter()
+            .min_by_key(|&&other| adjacency[other].len())
+            .unwrap();
+        let candidate_eccentricity = level_structure(adjacency, candidate).len();
+        if candidate_eccentricity <= eccentricity {
+            return node;
+        }
+        node = candidate;
+        eccentricity = candidate_eccentricity;
+    }
+}
+
+pub fn reverse_cuthill_mckee(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let mut visited = vec![false; adjacency.len()];
+    let mut order = Vec::with_capacity(adjacency.len());
+    let mut by_degree: Vec<usize> = (0..adjacency.len()).collect();
+    by_degree.sort_by_key(|&node| adjacency[node].len());
+
+    for start in by_degree {
+        if visited[start] {
+            continue;
+        }
+
+        let start = pseudo_peripheral_node(adjacency, start);
+        visited[start] = true;
+        let mut head = order.len();
+        order.push(start);
+        while head < order.len() {
+            let node = order[head];
+            head += 1;
+
+            let mut next: Vec<usize> = adjacency[node]
+                .iter()
+                .copied()
+                .filter(|&neighbor| !visited[neighbor])
+                .collect();
+            next.sort_by_key(|&neighbor| adjacency[neighbor].len());
+            for neighbor in next {
+                visited[neighbor] = true;
+                order.push(neighbor);
+            }
+        }
+    }
+
+    let mut new_index = vec![0; adjacency.len()];
+    for (index, &node) in order.iter().rev().enumerate() {
+        new_index[node] = index;
+    }
+    new_index
+}
+
+pub fn permute(values: &[f64], new_index: &[usize]) -> Vec<f64> {
+    let mut permuted = vec![0.0; values.len()];
+    for (node, &value) in values.iter().enumerate() {
+        permuted[new_index[node]] = value;
+    }
+    permuted
+}
+
+pub fn renumber(mesh: &Mesh, new_index: &[usize]) -> Mesh {
+    let mut points = vec![(0.0, 0.0); mesh.points.len()];
+    for (node, &point) in mesh.points.iter().enumerate() {
+        points[new_index[node]] = point;
+    }
+
+    let elements = mesh
+        .elements
+        .iter()
+        .map(|element| {
+            let material = materials::material_entry(element);
+            element
+                .iter()
+                .enumerate()
+                .map(|(entry, &node)| {
+                    if Some(entry) == material {
+                        node
+                    } else {
+                        new_index[node]
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let dirichlet = mesh.dirichlet.iter().map(|&node| new_index[node]).collect();
+    let renumber_edges = |edges: &[Vec<usize>]| -> Vec<Vec<usize>> {
+        edges
+            .iter()
+            .map(|edge| edge.iter().map(|&node| new_index[node]).collect())
+            .collect()
+    };
+    let renumber_values = |values: &EdgeValues| {
+        values
+            .iter()
+            .map(|(&(a, b), value)| {
+                let (a, b) = (new_index[a], new_index[b]);
+                ((a.min(b), a.max(b)), value.clone())
+            })
+            .collect()
+    };
+    let fields = mesh
+        .fields
+        .iter()
+        .map(|field| {
+            let values = match field.location {
+                FieldLocation::Node => permute(&field.values, new_index),
+                FieldLocation::Element => field.values.clone(),
+            };
+            Field::new(&field.name, &field.source, values)
+        })
+        .collect();
+
+    let mut renumbered = Mesh::new(
+        points,
+        elements,
+        dirichlet,
+        renumber_edges(&mesh.neumann),
+        fields,
+    );
+    renumbered.robin = renumber_edges(&mesh.robin);
+    renumbered.conditions = Conditions {
+        dirichlet: mesh
+            .conditions
+            .dirichlet
+            .iter()
+            .map(|(&node, value)| (new_index[node], value.clone()))
+            .collect(),
+        neumann: renumber_values(&mesh.conditions.neumann),
+        robin: renumber_values(&mesh.conditions.robin),
+    };
+    renumbered.update_uncovered_edges();
+    renumbered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loaders;
+
+    const GRIDS: [&str; 3] = ["grid", "grid/hole", "grid/test_grid"];
+
+    fn load(name: &str) -> Mesh {
+        loaders::load_mesh(&format!("{}/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+    }
+
+    fn inverse(new_index: &[usize]) -> Vec<usize> {
+        let mut old_index = vec![0; new_index.len()];
+        for (node, &index) in new_index.iter().enumerate() {
+            old_index[index] = node;
+        }
+        old_index
+    }
+
+    #[test]
+    fn reverse_cuthill_mckee_does_not_increase_bandwidth() {
+        for name in GRIDS {
+            let mesh = load(name);
+            let sparsity = Sparsity::new(&mesh.elements, mesh.points.len());
+            let mut sorted = sparsity.renumbering.clone();
+            sorted.sort_unstable();
+            assert!(sorted.iter().copied().eq(0..mesh.points.len()), "{}", name);
+            assert!(
+                sparsity.renumbered.bandwidth <= sparsity.statistics.bandwidth,
+                "{}",
+                name
+            );
+            assert_eq!(
+                sparsity.renumbered.nonzeros, sparsity.statistics.nonzeros,
+                "{}",
+                name
+            );
+
+            // A scrambled numbering must be brought back to a narrow band as well.
+            let scrambled: Vec<usize> = (0..mesh.points.len())
+                .map(|node| node * 7919 % mesh.points.len())
+                .collect();
+            let shuffled = renumber(&mesh, &scrambled);
+            let shuffled = Sparsity::new(&shuffled.elements, shuffled.points.len());
+            assert!(shuffled.statistics.bandwidth > shuffled.renumbered.bandwidth);
+            assert!(shuffled.renumbered.bandwidth <= sparsity.statistics.bandwidth);
+        }
+    }
+
+    #[test]
+    fn renumber_round_trips() {
+        for name in GRIDS {
+            let mesh = load(name);
+            let new_index = Sparsity::new(&mesh.elements, mesh.points.len()).renumbering;
+            let renumbered = renumber(&mesh, &new_index);
+            let restored = renumber(&renumbered, &inverse(&new_index));
+
+            assert_eq!(restored.points, mesh.points, "{}", name);
+            assert_eq!(restored.elements, mesh.elements, "{}", name);
+            assert_eq!(restored.dirichlet, mesh.dirichlet, "{}", name);
+            assert_eq!(restored.neumann, mesh.neumann, "{}", name);
+            assert_eq!(restored.robin, mesh.robin, "{}", name);
+            assert!(restored.conditions == mesh.conditions, "{}", name);
+            for (restored, field) in restored.fields.iter().zip(&mesh.fields) {
+                assert_eq!(restored.values, field.values, "{}", name);
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::interpolation;
+
+pub struct MaterialStatistics {
+    pub id: usize,
+    pub area: f64,
+    pub integral: f64,
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
+impl MaterialStatistics {
+    pub fn mean(&self) -> f64 {
+        self.integral / self.area
+    }
+}
+
+pub fn material_statistics(
+    points: &[(f64, f64)],
+    triangles: &[Vec<usize>],
+    triangle_materials: impl Iterator<Item = Option<usize>>,
+    triangle_values: impl Iterator<Item = [f64; 3]>,
+) -> Vec<MaterialStatistics> {
+    let mut statistics: Vec<MaterialStatistics> = Vec::new();
+
+    for ((triangle, id), vertex_values) in triangles
+        .iter()
+        .zip(triangle_materials)
+        .zip(triangle_values)
+    {
+        let Some(id) = id else {
+            continue;
+        };
+
+        let area = interpolation::triangle_area(points, triangle);
+        let integral = area * vertex_values.iter().sum::<f64>() / 3.0;
+        let minimum = vertex_values.iter().copied().fold(f64::INFINITY, f64::min);
+        let maximum = vertex_values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        match statistics.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.area += area;
+                entry.integral += integral;
+                entry.minimum = entry.minimum.min(minimum);
+                entry.maximum = entry.maximum.max(maximum);
+            }
+            None => statistics.push(MaterialStatistics {
+                id,
+                area,
+                integral,
+                minimum,
+                maximum,
+            }),
+        }
+    }
+
+    statistics.sort_by_key(|entry| entry.id);
+    statistics
+}
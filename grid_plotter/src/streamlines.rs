@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use crate::interpolation;
+
+pub fn triangle_neighbors(triangles: &[Vec<usize>]) -> Vec<[Option<usize>; 3]> {
+    let mut edges: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+
+    for (i, triangle) in triangles.iter().enumerate() {
+        for k in 0..3 {
+            let a = triangle[(k + 1) % 3];
+            let b = triangle[(k + 2) % 3];
+            edges.entry((a.min(b), a.max(b))).or_default().push((i, k));
+        }
+    }
+
+    let mut neighbors = vec![[None; 3]; triangles.len()];
+    for shared in edges.values() {
+        if let [(first, first_side), (second, second_side)] = shared[..] {
+            neighbors[first][first_side] = Some(second);
+            neighbors[second][second_side] = Some(first);
+        }
+    }
+
+    neighbors
+}
+
+pub fn walk(
+    points: &[(f64, f64)],
+    triangles: &[Vec<usize>],
+    neighbors: &[[Option<usize>; 3]],
+    start: usize,
+    p: [f64; 2],
+) -> Option<usize> {
+    let mut current = start;
+
+    for _ in 0..triangles.len() {
+        let coordinates = interpolation::barycentric(points, &triangles[current], p);
+        if interpolation::contains(coordinates) {
+            return Some(current);
+        }
+
+        let side = (0..3)
+            .min_by(|&a, &b| coordinates[a].total_cmp(&coordinates[b]))
+            .unwrap();
+        current = neighbors[current][side]?;
+    }
+
+    None
+}
+
+pub fn trace(
+    points: &[(f64, f64)],
+    triangles: &[Vec<usize>],
+    neighbors: &[[Option<usize>; 3]],
+    directions: &[[f64; 2]],
+    seed: [f64; 2],
+    step: f64,
+    max_steps: usize,
+) -> Vec<[f64; 2]> {
+    let Some((mut triangle, _)) = interpolation::locate_triangle(points, triangles, seed) else {
+        return Vec::new();
+    };
+
+    let velocity = |start: usize, p: [f64; 2]| -> Option<(usize, [f64; 2])> {
+        let found = walk(points, triangles, neighbors, start, p)?;
+        let v = directions[found];
+        let length = v[0].hypot(v[1]);
+        (length > 0.0).then_some((found, [v[0] / length, v[1] / length]))
+    };
+    let shift = |p: [f64; 2], v: [f64; 2], h: f64| [p[0] + h * v[0], p[1] + h * v[1]];
+
+    let mut p = seed;
+    let mut polyline = vec![p];
+
+    for _ in 0..max_steps {
+        let Some((found, k1)) = velocity(triangle, p) else {
+            break;
+        };
+        triangle = found;
+        let Some((_, k2)) = velocity(triangle, shift(p, k1, step / 2.0)) else {
+            break;
+        };
+        let Some((_, k3)) = velocity(triangle, shift(p, k2, step / 2.0)) else {
+            break;
+        };
+        let Some((_, k4)) = velocity(triangle, shift(p, k3, step)) else {
+            break;
+        };
+
+        let direction = [
+            (k1[0] + 2.0 * k2[0] + 2.0 * k3[0] + k4[0]) / 6.0,
+            (k1[1] + 2.0 * k2[1] + 2.0 * k3[1] + k4[1]) / 6.0,
+        ];
+        if direction[0].hypot(direction[1]) < 1e-3 {
+            break;
+        }
+
+        p = shift(p, direction, step);
+        polyline.push(p);
+    }
+
+    polyline
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::TAU;
+
+use crate::boundary::{self, Conditions, EdgeValues};
+use crate::field::{Field, FieldLocation, SOLUTION_FIELD};
+use crate::interpolation;
+use crate::materials;
+use crate::mesh::{self, Mesh};
+
+const MERGE_TOLERANCE: f64 = 1e-9;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(default)]
+pub struct Symmetry {
+    pub mirror_x: bool,
+    pub mirror_y: bool,
+    pub negate_x: bool,
+    pub negate_y: bool,
+    pub copies: u16,
+    pub center: [f64; 2],
+}
+
+impl Default for Symmetry {
+    fn default() -> Self {
+        Self {
+            mirror_x: false,
+            mirror_y: false,
+            negate_x: false,
+            negate_y: false,
+            copies: 1,
+            center: [0.0, 0.0],
+        }
+    }
+}
+
+struct Image {
+    matrix: [[f64; 2]; 2],
+    sign: f64,
+}
+
+impl Image {
+    fn reflect(&self, axis: usize, sign: f64) -> Self {
+        let mut matrix = self.matrix;
+        matrix[1 - axis] = [-matrix[1 - axis][0], -matrix[1 - axis][1]];
+        Self {
+            matrix,
+            sign: self.sign * sign,
+        }
+    }
+
+    fn rotate(&self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let [a, b] = self.matrix;
+        Self {
+            matrix: [
+                [cos * a[0] - sin * b[0], cos * a[1] - sin * b[1]],
+                [sin * a[0] + cos * b[0], sin * a[1] + cos * b[1]],
+            ],
+            sign: self.sign,
+        }
+    }
+
+    fn apply(&self, center: [f64; 2], (x, y): (f64, f64)) -> (f64, f64) {
+        let (dx, dy) = (x - center[0], y - center[1]);
+        let [a, b] = self.matrix;
+        (
+            center[0] + a[0] * dx + a[1] * dy,
+            center[1] + b[0] * dx + b[1] * dy,
+        )
+    }
+}
+
+impl Symmetry {
+    pub fn is_identity(&self) -> bool {
+        !self.mirror_x && !self.mirror_y && self.copies <= 1
+    }
+
+    pub fn flips_sign(&self) -> bool {
+        (self.mirror_x && self.negate_x) || (self.mirror_y && self.negate_y)
+    }
+
+    pub fn value_range(&self, (minimum, maximum): (f64, f64)) -> (f64, f64) {
+        if self.flips_sign() {
+            (minimum.min(-maximum), maximum.max(-minimum))
+        } else {
+            (minimum, maximum)
+        }
+    }
+
+    fn images(&self) -> Vec<Image> {
+        let mut reflections = vec![Image {
+            matrix: [[1.0, 0.0], [0.0, 1.0]],
+            sign: 1.0,
+        }];
+        for (axis, mirror, negate) in [
+            (0, self.mirror_x, self.negate_x),
+            (1, self.mirror_y, self.negate_y),
+        ] {
+            if mirror {
+                let sign = if negate { -1.0 } else { 1.0 };
+                let reflected: Vec<Image> = reflections
+                    .iter()
+                    .map(|image| image.reflect(axis, sign))
+                    .collect();
+                reflections.extend(reflected);
+            }
+        }
+
+        let copies = self.copies.max(1);
+        (0..copies)
+            .flat_map(|copy| {
+                let angle = TAU * copy as f64 / copies as f64;
+                reflections.iter().map(move |image| image.rotate(angle))
+            })
+            .collect()
+    }
+}
+
+fn replicate_edges(
+    edges: &[Vec<usize>],
+    values: &EdgeValues,
+    index: &[usize],
+    replicated: &mut Vec<Vec<usize>>,
+    replicated_values: &mut EdgeValues,
+) {
+    for edge in edges {
+        let mapped: Vec<usize> = edge.iter().map(|&node| index[node]).collect();
+        if mapped.len() >= 2 {
+            if let Some(value) = values.get(&boundary::edge_key(edge)) {
+                replicated_values.insert(boundary::edge_key(&mapped), value.clone());
+            }
+        }
+        replicated.push(mapped);
+    }
+}
+
+pub fn replicate(mesh: &Mesh, symmetry: &Symmetry) -> Mesh {
+    let (min, max) = interpolation::bounding_box(&mesh.points);
+    let tolerance =
+        (MERGE_TOLERANCE * (max[0] - min[0]).hypot(max[1] - min[1])).max(f64::MIN_POSITIVE);
+    let images = symmetry.images();
+
+    let mut points = Vec::with_capacity(images.len() * mesh.points.len());
+    let mut sources: Vec<(usize, f64)> = Vec::with_capacity(points.capacity());
+    let mut merged: HashMap<(i64, i64), usize> = HashMap::new();
+    let indices: Vec<Vec<usize>> = images
+        .iter()
+        .map(|image| {
+            mesh.points
+                .iter()
+                .enumerate()
+                .map(|(node, &point)| {
+                    let point = image.apply(symmetry.center, point);
+                    let key = (
+                        (point.0 / tolerance).round() as i64,
+                        (point.1 / tolerance).round() as i64,
+                    );
+                    *merged.entry(key).or_insert_with(|| {
+                        points.push(point);
+                        sources.push((node, image.sign));
+                        points.len() - 1
+                    })
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut elements = Vec::with_capacity(images.len() * mesh.elements.len());
+    let mut parents = Vec::with_capacity(elements.capacity());
+    for (image, index) in images.iter().zip(&indices) {
+        for (parent, element) in mesh.elements.iter().enumerate() {
+            let material = materials::material_entry(element);
+            let element: Vec<usize> = element
+                .iter()
+                .enumerate()
+                .map(|(entry, &node)| {
+                    if Some(entry) == material {
+                        node
+                    } else {
+                        index[node]
+                    }
+                })
+                .collect();
+            let mut key = mesh::element_nodes(&element);
+            key.sort_unstable();
+            if seen.insert(key) {
+                elements.push(element);
+                parents.push((parent, image.sign));
+            }
+        }
+    }
+
+    let fields = mesh
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let sign = |sign: f64| if index == SOLUTION_FIELD { sign } else { 1.0 };
+            let values = match field.location {
+                FieldLocation::Node => sources
+                    .iter()
+                    .map(|&(node, image_sign)| sign(image_sign) * field.values[node])
+                    .collect(),
+                FieldLocation::Element => parents
+                    .iter()
+                    .map(|&(parent, image_sign)| sign(image_sign) * field.values[parent])
+                    .collect(),
+            };
+            Field::new(&field.name, &field.source, values)
+        })
+        .collect();
+
+    let mut dirichlet = Vec::new();
+    let mut conditions = Conditions::default();
+    let mut neumann = Vec::new();
+    let mut robin = Vec::new();
+    let mut dirichlet_nodes = HashSet::new();
+    for index in &indices {
+        for &node in &mesh.dirichlet {
+            if dirichlet_nodes.insert(index[node]) {
+                dirichlet.push(index[node]);
+                if let Some(value) = mesh.conditions.dirichlet.get(&node) {
+                    conditions.dirichlet.insert(index[node], value.clone());
+                }
+            }
+        }
+        replicate_edges(
+            &mesh.neumann,
+            &mesh.conditions.neumann,
+            index,
+            &mut neumann,
+            &mut conditions.neumann,
+        );
+        replicate_edges(
+            &mesh.robin,
+            &mesh.conditions.robin,
+            index,
+            &mut robin,
+            &mut conditions.robin,
+        );
+    }
+
+    let mut replicated = Mesh::new(points, elements, dirichlet, neumann, fields);
+    replicated.robin = robin;
+    replicated.conditions = conditions;
+    replicated.update_uncovered_edges();
+    replicated
+}
@@ -0,0 +1,84 @@
+pub struct Timeline {
+    pub times: Vec<f64>,
+    pub layers: Vec<Vec<f64>>,
+    pub current: usize,
+    pub playing: bool,
+    pub looping: bool,
+    pub frames_per_second: f32,
+    pub elapsed: f32,
+}
+
+impl Timeline {
+    pub fn new(times: Vec<f64>, layers: Vec<Vec<f64>>) -> Self {
+        Self {
+            times,
+            layers,
+            current: 0,
+            playing: false,
+            looping: true,
+            frames_per_second: 5.0,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn frames_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn validate(&self, nodes_count: usize) -> Result<(), String> {
+        if self.layers.is_empty() {
+            return Err("the solution series has no time layers".to_string());
+        }
+        if self.times.len() != self.layers.len() {
+            return Err(format!(
+                "the solution series has {} times but {} layers",
+                self.times.len(),
+                self.layers.len()
+            ));
+        }
+        match self
+            .layers
+            .iter()
+            .position(|layer| layer.len() != nodes_count)
+        {
+            Some(index) => Err(format!(
+                "time layer {} has {} values but the mesh has {} nodes",
+                index,
+                self.layers[index].len(),
+                nodes_count
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn advance(&mut self, dt: f32) -> Option<usize> {
+        if !self.playing || self.frames_count() < 2 {
+            return None;
+        }
+
+        self.elapsed += dt;
+        let frame_duration = 1.0 / self.frames_per_second;
+        if self.elapsed < frame_duration {
+            return None;
+        }
+        self.elapsed = 0.0;
+
+        if self.current + 1 < self.frames_count() {
+            Some(self.current + 1)
+        } else if self.looping {
+            Some(0)
+        } else {
+            self.playing = false;
+            None
+        }
+    }
+
+    pub fn range(&self) -> (f64, f64) {
+        self.layers
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            })
+    }
+}
@@ -0,0 +1,220 @@
+use crate::field::FieldLocation;
+use crate::interpolation;
+use crate::mesh::Mesh;
+
+const NEWTON_ITERATIONS: usize = 20;
+const REFERENCE_TOLERANCE: f64 = 1e-9;
+
+pub struct DifferenceNorms {
+    pub maximum: f64,
+    pub l2: f64,
+    pub relative_l2: f64,
+    pub missing: usize,
+}
+
+fn lagrange(t: f64) -> [f64; 3] {
+    [t * (t - 1.0) / 2.0, 1.0 - t * t, t * (t + 1.0) / 2.0]
+}
+
+pub fn shape_functions(element: &[usize], xi: f64, eta: f64) -> Vec<(usize, f64)> {
+    match element.len() {
+        10 => {
+            const LATTICE: [(usize, usize, usize); 9] = [
+                (0, 0, 0),
+                (1, 1, 0),
+                (2, 2, 0),
+                (3, 2, 1),
+                (4, 2, 2),
+                (5, 1, 2),
+                (6, 0, 2),
+                (7, 0, 1),
+                (9, 1, 1),
+            ];
+            let (u, v) = (lagrange(xi), lagrange(eta));
+            LATTICE
+                .iter()
+                .map(|&(entry, i, j)| (entry, u[i] * v[j]))
+                .collect()
+        }
+        9 => {
+            const NODES: [(f64, f64); 8] = [
+                (-1.0, -1.0),
+                (0.0, -1.0),
+                (1.0, -1.0),
+                (1.0, 0.0),
+                (1.0, 1.0),
+                (0.0, 1.0),
+                (-1.0, 1.0),
+                (-1.0, 0.0),
+            ];
+            NODES
+                .iter()
+                .enumerate()
+                .map(|(entry, &(a, b))| {
+                    let value = if a == 0.0 {
+                        (1.0 - xi * xi) * (1.0 + eta * b) / 2.0
+                    } else if b == 0.0 {
+                        (1.0 + xi * a) * (1.0 - eta * eta) / 2.0
+                    } else {
+                        (1.0 + xi * a) * (1.0 + eta * b) * (xi * a + eta * b - 1.0) / 4.0
+                    };
+                    (entry, value)
+                })
+                .collect()
+        }
+        5 => [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .iter()
+            .enumerate()
+            .map(|(entry, &(a, b))| (entry, (1.0 + xi * a) * (1.0 + eta * b) / 4.0))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn map_to_physical(points: &[(f64, f64)], element: &[usize], xi: f64, eta: f64) -> [f64; 2] {
+    shape_functions(element, xi, eta)
+        .iter()
+        .fold([0.0, 0.0], |sum, &(entry, value)| {
+            let (x, y) = points[element[entry]];
+            [sum[0] + value * x, sum[1] + value * y]
+        })
+}
+
+pub fn reference_coordinates(
+    points: &[(f64, f64)],
+    element: &[usize],
+    p: [f64; 2],
+) -> Option<[f64; 2]> {
+    let h = 1e-7;
+    let (mut xi, mut eta) = (0.0, 0.0);
+
+    for _ in 0..NEWTON_ITERATIONS {
+        let current = map_to_physical(points, element, xi, eta);
+        let residual = [p[0] - current[0], p[1] - current[1]];
+
+        let along_xi = map_to_physical(points, element, xi + h, eta);
+        let along_eta = map_to_physical(points, element, xi, eta + h);
+        let jacobian = [
+            [
+                (along_xi[0] - current[0]) / h,
+                (along_eta[0] - current[0]) / h,
+            ],
+            [
+                (along_xi[1] - current[1]) / h,
+                (along_eta[1] - current[1]) / h,
+            ],
+        ];
+        let determinant = jacobian[0][0] * jacobian[1][1] - jacobian[0][1] * jacobian[1][0];
+        if determinant.abs() < 1e-300 {
+            return None;
+        }
+
+        let d_xi = (jacobian[1][1] * residual[0] - jacobian[0][1] * residual[1]) / determinant;
+        let d_eta = (jacobian[0][0] * residual[1] - jacobian[1][0] * residual[0]) / determinant;
+        xi += d_xi;
+        eta += d_eta;
+
+        if d_xi.abs().max(d_eta.abs()) < 1e-12 {
+            break;
+        }
+    }
+
+    let inside = xi.abs() <= 1.0 + REFERENCE_TOLERANCE && eta.abs() <= 1.0 + REFERENCE_TOLERANCE;
+    inside.then_some([xi, eta])
+}
+
+pub fn locate_element(mesh: &Mesh, p: [f64; 2]) -> Option<(usize, [f64; 2])> {
+    mesh.elements
+        .iter()
+        .enumerate()
+        .find_map(|(index, element)| {
+            let outline: Vec<(f64, f64)> = crate::mesh::element_outline(element)
+                .iter()
+                .map(|&node| mesh.points[node])
+                .collect();
+            if outline.is_empty() {
+                return None;
+            }
+
+            let (min, max) = interpolation::bounding_box(&outline);
+            let margin = 1e-9 * (max[0] - min[0]).hypot(max[1] - min[1]);
+            if p[0] < min[0] - margin
+                || p[0] > max[0] + margin
+                || p[1] < min[1] - margin
+                || p[1] > max[1] + margin
+            {
+                return None;
+            }
+
+            reference_coordinates(&mesh.points, element, p).map(|coordinates| (index, coordinates))
+        })
+}
+
+pub fn evaluate(mesh: &Mesh, field: usize, p: [f64; 2]) -> Option<f64> {
+    let (index, [xi, eta]) = locate_element(mesh, p)?;
+    let element = &mesh.elements[index];
+    let field = &mesh.fields[field];
+
+    match field.location {
+        FieldLocation::Element => Some(field.values[index]),
+        FieldLocation::Node => Some(
+            shape_functions(element, xi, eta)
+                .iter()
+                .map(|&(entry, value)| value * field.values[element[entry]])
+                .sum(),
+        ),
+    }
+}
+
+pub fn transfer(source: &Mesh, field: usize, target: &Mesh) -> Vec<Option<f64>> {
+    target
+        .points
+        .iter()
+        .map(|&(x, y)| evaluate(source, field, [x, y]))
+        .collect()
+}
+
+pub fn difference_norms(
+    target: &Mesh,
+    reference: &[f64],
+    transferred: &[Option<f64>],
+) -> (Vec<f64>, DifferenceNorms) {
+    let difference: Vec<f64> = reference
+        .iter()
+        .zip(transferred)
+        .map(|(&value, other)| other.map_or(0.0, |other| value - other))
+        .collect();
+
+    let integrate_square = |values: &[f64]| -> f64 {
+        target
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = [
+                    values[triangle[0]],
+                    values[triangle[1]],
+                    values[triangle[2]],
+                ];
+                interpolation::triangle_area(&target.points, triangle) / 6.0
+                    * (a * a + b * b + c * c + a * b + b * c + c * a)
+            })
+            .sum()
+    };
+
+    let l2 = integrate_square(&difference).sqrt();
+    let reference_l2 = integrate_square(reference).sqrt();
+    let norms = DifferenceNorms {
+        maximum: difference
+            .iter()
+            .fold(0.0, |max, value| max.max(value.abs())),
+        l2,
+        relative_l2: if reference_l2 > 0.0 {
+            l2 / reference_l2
+        } else {
+            0.0
+        },
+        missing: transferred.iter().filter(|value| value.is_none()).count(),
+    };
+
+    (difference, norms)
+}
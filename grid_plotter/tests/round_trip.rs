@@ -0,0 +1,343 @@
+use std::path::{Path, PathBuf};
+
+use grid_plotter::boundary::{self, BoundaryValue};
+use grid_plotter::timeline::Timeline;
+use grid_plotter::{loaders, refinement};
+use grid_plotter::{Field, FieldLocation, Mesh};
+
+fn grid_folder(name: &str) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("grid");
+    if !name.is_empty() {
+        path.push(name);
+    }
+    path.to_string_lossy().into_owned()
+}
+
+fn output_folder(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!(
+        "grid_plotter_round_trip_{}_{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&path);
+    path.to_string_lossy().into_owned()
+}
+
+fn assert_same_mesh(expected: &Mesh, actual: &Mesh) {
+    assert_eq!(expected.points, actual.points);
+    assert_eq!(expected.elements, actual.elements);
+    assert_eq!(expected.dirichlet, actual.dirichlet);
+    assert_eq!(expected.neumann, actual.neumann);
+    assert_eq!(expected.robin, actual.robin);
+    assert!(expected.conditions == actual.conditions);
+    assert_eq!(expected.fields.len(), actual.fields.len());
+    for (expected, actual) in expected.fields.iter().zip(&actual.fields) {
+        assert_eq!(expected.name, actual.name);
+        assert_eq!(expected.values, actual.values);
+    }
+}
+
+fn round_trip(name: &str) {
+    let original = loaders::load_mesh(&grid_folder(name)).unwrap();
+
+    let label = if name.is_empty() { "grid" } else { name };
+    let folder = output_folder(label);
+    loaders::save_mesh(&original, &folder).unwrap();
+    let saved = loaders::load_mesh(&folder).unwrap();
+    assert_same_mesh(&original, &saved);
+
+    let again = output_folder(&format!("{}_again", label));
+    loaders::save_mesh(&saved, &again).unwrap();
+    for file in [
+        "points",
+        "finite_elements",
+        "dirichlet",
+        "neumann",
+        "solution",
+    ] {
+        assert_eq!(
+            std::fs::read(format!("{}/{}", folder, file)).unwrap(),
+            std::fs::read(format!("{}/{}", again, file)).unwrap(),
+            "{} differs after a second round trip",
+            file
+        );
+    }
+
+    std::fs::remove_dir_all(&folder).unwrap();
+    std::fs::remove_dir_all(&again).unwrap();
+}
+
+#[test]
+fn round_trip_grid() {
+    round_trip("");
+}
+
+#[test]
+fn round_trip_hole() {
+    round_trip("hole");
+}
+
+#[test]
+fn round_trip_test_grid() {
+    round_trip("test_grid");
+}
+
+#[test]
+fn round_trip_extra_fields() {
+    let mut mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    let values: Vec<f64> = mesh.points.iter().map(|&(x, y)| x * y + 0.1).collect();
+    let cells: Vec<f64> = (0..mesh.elements.len()).map(|i| i as f64 / 3.0).collect();
+    mesh.set_field(Field::new("product", "", values));
+    let mut cells = Field::new("cells", "", cells);
+    cells.location = FieldLocation::Element;
+    mesh.set_field(cells);
+
+    let folder = output_folder("extra_fields");
+    loaders::save_mesh(&mesh, &folder).unwrap();
+    let saved = loaders::load_mesh(&folder).unwrap();
+
+    for field in &mesh.fields[1..] {
+        let index = saved.field_index(&field.name).unwrap();
+        assert_eq!(field.values, saved.fields[index].values);
+        assert!(field.location == saved.fields[index].location);
+    }
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn round_trip_boundary_values() {
+    let mut mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    mesh.robin = mesh.neumann.split_off(4);
+    for &node in &mesh.dirichlet {
+        let value = BoundaryValue {
+            type_id: 1 + node % 2,
+            values: vec![mesh.points[node].0 * 0.3],
+        };
+        mesh.conditions.dirichlet.insert(node, value);
+    }
+    for edge in &mesh.neumann {
+        let value = BoundaryValue {
+            type_id: 3,
+            values: vec![-1.5],
+        };
+        mesh.conditions
+            .neumann
+            .insert(boundary::edge_key(edge), value);
+    }
+    for edge in &mesh.robin {
+        let value = BoundaryValue {
+            type_id: 4,
+            values: vec![2.0, 0.1],
+        };
+        mesh.conditions
+            .robin
+            .insert(boundary::edge_key(edge), value);
+    }
+    mesh.update_uncovered_edges();
+
+    let folder = output_folder("boundary_values");
+    loaders::save_mesh(&mesh, &folder).unwrap();
+    let saved = loaders::load_mesh(&folder).unwrap();
+    assert_same_mesh(&mesh, &saved);
+    assert_eq!(mesh.uncovered_edges, saved.uncovered_edges);
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn save_rejects_empty_folder() {
+    let mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    for folder in ["", "  "] {
+        let error = loaders::save_mesh(&mesh, folder).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn load_rejects_mismatched_solution() {
+    let mut mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    let folder = output_folder("mismatched_solution");
+    mesh.fields[0].values.pop();
+    loaders::save_mesh(&mesh, &folder).unwrap();
+
+    let error = loaders::load_mesh(&folder).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn mesh_keeps_nodal_solution_first() {
+    let mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    let elements = vec![0.0; mesh.elements.len()];
+    let odd = vec![0.0; mesh.points.len() + 1];
+    let rebuilt = Mesh::new(
+        mesh.points.clone(),
+        mesh.elements.clone(),
+        mesh.dirichlet.clone(),
+        mesh.neumann.clone(),
+        vec![
+            Field::new("solution", "", elements),
+            Field::new("odd", "", odd),
+        ],
+    );
+
+    assert_eq!(rebuilt.fields.len(), 1);
+    assert_eq!(rebuilt.fields[0].values.len(), mesh.points.len());
+    assert!(rebuilt.fields[0].location == FieldLocation::Node);
+    assert_eq!(rebuilt.skipped_fields.len(), 2);
+    assert!(Mesh::default().fields.len() == 1);
+}
+
+#[test]
+fn round_trip_timeline() {
+    let mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    let layers: Vec<Vec<f64>> = [0.0, 0.5, 2.0]
+        .iter()
+        .map(|t| mesh.points.iter().map(|&(x, y)| t * x - y).collect())
+        .collect();
+    let timeline = Timeline::new(vec![0.0, 0.5, 2.0], layers);
+
+    let file_folder = output_folder("timeline_file");
+    loaders::save_mesh(&mesh, &file_folder).unwrap();
+    loaders::save_timeline(&timeline, &file_folder).unwrap();
+
+    let folder_folder = output_folder("timeline_folder");
+    loaders::save_mesh(&mesh, &folder_folder).unwrap();
+    let solutions = Path::new(&folder_folder).join("solutions");
+    std::fs::create_dir_all(&solutions).unwrap();
+    std::fs::write(solutions.join("solution_7"), "1\n").unwrap();
+    loaders::save_timeline(&timeline, &folder_folder).unwrap();
+
+    for folder in [&file_folder, &folder_folder] {
+        let saved = loaders::load_timeline(Path::new(folder), mesh.points.len())
+            .unwrap()
+            .unwrap();
+        assert_eq!(timeline.times, saved.times);
+        assert_eq!(timeline.layers, saved.layers);
+        std::fs::remove_dir_all(folder).unwrap();
+    }
+}
+
+#[test]
+fn round_trip_refined_timeline() {
+    let mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    let layers: Vec<Vec<f64>> = [1.0, 3.0]
+        .iter()
+        .map(|t| {
+            mesh.points
+                .iter()
+                .map(|&(x, y)| t * (x + 2.0 * y))
+                .collect()
+        })
+        .collect();
+    let refinement = refinement::refine(&mesh).unwrap();
+    let refined_layers: Vec<Vec<f64>> = layers
+        .iter()
+        .map(|layer| refinement.node_values(layer))
+        .collect();
+    let timeline = Timeline::new(vec![1.0, 3.0], refined_layers);
+
+    let folder = output_folder("refined_timeline");
+    loaders::save_mesh(&refinement.mesh, &folder).unwrap();
+    loaders::save_timeline(&timeline, &folder).unwrap();
+
+    let saved = loaders::load_mesh(&folder).unwrap();
+    let saved_timeline = loaders::load_timeline(Path::new(&folder), saved.points.len())
+        .unwrap()
+        .unwrap();
+    assert_eq!(saved.points.len(), refinement.mesh.points.len());
+    assert_eq!(timeline.layers, saved_timeline.layers);
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn load_rejects_misaligned_boundary_values() {
+    let mut mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    for &node in &mesh.dirichlet {
+        mesh.conditions
+            .dirichlet
+            .insert(node, BoundaryValue::default());
+    }
+    let folder = output_folder("misaligned_values");
+    loaders::save_mesh(&mesh, &folder).unwrap();
+    let values = Path::new(&folder).join("dirichlet_values");
+    let text = std::fs::read_to_string(&values).unwrap();
+    std::fs::write(&values, text.lines().skip(1).collect::<Vec<_>>().join("\n")).unwrap();
+
+    let error = loaders::load_mesh(&folder).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn load_rejects_malformed_files() {
+    let mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    for (file, text) in [
+        ("points", "0 0\n1\n"),
+        ("points", "0 zero\n"),
+        ("finite_elements", "0 1 x 4 1\n"),
+        ("dirichlet", "0\n\n"),
+        ("neumann", "1 -2\n"),
+    ] {
+        let folder = output_folder(&format!("malformed_{}", file));
+        loaders::save_mesh(&mesh, &folder).unwrap();
+        std::fs::write(Path::new(&folder).join(file), text).unwrap();
+
+        let error = loaders::load_mesh(&folder).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "{}", file);
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}
+
+#[test]
+fn load_rejects_missing_nodes() {
+    let mesh = loaders::load_mesh(&grid_folder("test_grid")).unwrap();
+    let missing = mesh.points.len();
+    for (file, text) in [
+        ("finite_elements", format!("0 1 {} 4 1\n", missing)),
+        ("dirichlet", format!("0\n{}\n", missing)),
+        ("neumann", format!("0 {}\n", missing)),
+        ("robin", format!("0 {}\n", missing)),
+    ] {
+        let folder = output_folder(&format!("missing_{}", file));
+        loaders::save_mesh(&mesh, &folder).unwrap();
+        std::fs::write(Path::new(&folder).join(file), text).unwrap();
+
+        let error = loaders::load_mesh(&folder).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "{}", file);
+        assert!(
+            error.to_string().contains(&missing.to_string()),
+            "{}",
+            error
+        );
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    let folder = output_folder("missing_json");
+    std::fs::create_dir_all(&folder).unwrap();
+    let file = Path::new(&folder).join("mesh.json");
+    for json in [
+        r#"{"points": [[0, 0], [1, 0], [1, 1], [0, 1]], "elements": [[0, 1, 2, 4, 1]]}"#,
+        r#"{"points": [[0, 0], [1, 0], [1, 1], [0, 1]], "elements": [[0, 1, 2, 3, 1]], "dirichlet": [4]}"#,
+        r#"{"points": [[0, 0], [1, 0], [1, 1], [0, 1]], "elements": [[0, 1, 2, 3, 1]], "neumann": [[3, 4]]}"#,
+    ] {
+        std::fs::write(&file, json).unwrap();
+        let error = loaders::ReaderRegistry::default()
+            .read(&file)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "{}", json);
+        assert!(error.to_string().contains("node 4"), "{}", error);
+    }
+    std::fs::write(
+        &file,
+        r#"{"points": [[0, 0], [1, 0], [1, 1], [0, 1]], "elements": [[0, 1, 2, 3, 7]]}"#,
+    )
+    .unwrap();
+    assert!(loaders::ReaderRegistry::default().read(&file).is_ok());
+    std::fs::remove_dir_all(&folder).unwrap();
+}